@@ -1,24 +1,134 @@
 use std;
-use std::collections::HashMap;
 use std::clone::Clone;
-
+use std::collections::{HashMap,HashSet};
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use serde::{Serialize,Serializer,Deserialize,Deserializer};
+use serde::de;
+
+use error::GraphError;
+use slab::{self,Slab};
 use traits::*;
 
+/// Vertex of an `AdjacencyList`: slot index and generation of the slot.
 #[derive(PartialEq,Eq,Hash,Copy,Clone,Debug,PartialOrd,Ord,Serialize,Deserialize)]
-pub struct AdjacencyListVertexDescriptor(pub usize);
+pub struct AdjacencyListVertexDescriptor(pub usize,pub u32);
 
+/// Edge of an `AdjacencyList`: slot index and generation of the slot.
 #[derive(PartialEq,Eq,Hash,Copy,Clone,Debug,PartialOrd,Ord,Serialize,Deserialize)]
-pub struct AdjacencyListEdgeDescriptor(pub usize);
+pub struct AdjacencyListEdgeDescriptor(pub usize,pub u32);
+
+#[derive(Clone,Debug)]
+struct VertexEntry<N> {
+    label:      N,
+    out_edges:  Vec<AdjacencyListEdgeDescriptor>,
+    in_edges:   Vec<AdjacencyListEdgeDescriptor>,
+}
+
+#[derive(Clone,Debug)]
+struct EdgeEntry<E> {
+    label:  E,
+    from:   AdjacencyListVertexDescriptor,
+    to:     AdjacencyListVertexDescriptor,
+}
+
+/// What `add_edge` does if the graph already has an edge between the two vertices.
+pub enum ParallelEdges<E> {
+    /// Add another edge.
+    Allow,
+    /// Fail with `GraphError::EdgeExists`.
+    Reject,
+    /// Pass the label of the existing edge and the new label to the function and return the
+    /// existing edge.
+    Merge(Arc<Fn(&mut E,E) + Send + Sync>),
+}
+
+impl<E> ParallelEdges<E> {
+    /// `Merge` with the function `f`.
+    pub fn merge<F: Fn(&mut E,E) + Send + Sync + 'static>(f: F) -> Self {
+        return ParallelEdges::Merge(Arc::new(f));
+    }
+}
+
+impl<E> Clone for ParallelEdges<E> {
+    fn clone(&self) -> Self {
+        match *self {
+            ParallelEdges::Allow => return ParallelEdges::Allow,
+            ParallelEdges::Reject => return ParallelEdges::Reject,
+            ParallelEdges::Merge(ref f) => return ParallelEdges::Merge(f.clone()),
+        }
+    }
+}
+
+impl<E> fmt::Debug for ParallelEdges<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParallelEdges::Allow => f.write_str("Allow"),
+            ParallelEdges::Reject => f.write_str("Reject"),
+            ParallelEdges::Merge(_) => f.write_str("Merge(..)"),
+        }
+    }
+}
+
+/// Restrictions on the edges of an `AdjacencyList`. The default allows parallel edges and
+/// self-loops.
+#[derive(Clone,Debug)]
+pub struct EdgePolicy<E> {
+    pub parallel_edges: ParallelEdges<E>,
+    pub self_loops: bool,
+}
+
+impl<E> Default for EdgePolicy<E> {
+    fn default() -> Self {
+        return EdgePolicy{
+            parallel_edges: ParallelEdges::Allow,
+            self_loops: true,
+        };
+    }
+}
 
-#[derive(Clone,Debug,Serialize,Deserialize)]
-pub struct AdjacencyList<N,E> {
-    vertex_labels:  HashMap<AdjacencyListVertexDescriptor,N>,
-    edge_labels:    HashMap<AdjacencyListEdgeDescriptor,E>,
-    out_edges:      HashMap<AdjacencyListVertexDescriptor,Vec<AdjacencyListEdgeDescriptor>>,
-    in_edges:       HashMap<AdjacencyListVertexDescriptor,Vec<AdjacencyListEdgeDescriptor>>,
-    edges:          HashMap<AdjacencyListEdgeDescriptor,(AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor)>,
-    next_edge:      AdjacencyListEdgeDescriptor,
-    next_vertex:    AdjacencyListVertexDescriptor
+/// Graph stored as per-vertex lists of in and out edges.
+///
+/// The graph is directed by default. `AdjacencyList<V,E,UndirectedEdges>`, created with
+/// `undirected()`, is undirected: every edge is in the out and in edge list of both of its
+/// endpoints and the graph implements `UndirectedGraph`.
+///
+/// Vertices and edges live in slabs: dense vectors whose free slots are reused by later
+/// insertions. Descriptors carry the generation of their slot, so a descriptor of a removed
+/// element is rejected instead of aliasing whatever was inserted into the slot afterwards.
+/// Descriptors of the remaining elements stay valid across removals.
+///
+/// `vertices()` and `edges()` enumerate descriptors in ascending order. The order only depends on
+/// the sequence of operations performed on the graph, not on hashing, so it is the same on every
+/// run and survives cloning and serialization. Without removals this is insertion order.
+///
+/// Out and in edges are listed in insertion order. By default removing an edge moves the last
+/// edge of the list into its place. Graphs created with `with_ordered_edges` keep the relative
+/// order of the remaining edges instead, at the cost of a linear time removal.
+///
+/// Whether parallel edges and self-loops are accepted is decided by the `EdgePolicy` set with
+/// `with_edge_policy`.
+///
+/// The serialized form is versioned and independent of the internal layout: a list of vertices
+/// and a list of edges together with their descriptors, plus the free slots left by removals.
+/// The edge lists of the vertices are rebuilt on load and inconsistent input, e.g. an edge to a
+/// missing vertex or a slot index beyond the number of elements and free slots, is rejected.
+/// Descriptors, including the invalidity of those of removed elements, and the order of out
+/// edges of directed graphs are preserved. The edge policy is
+/// saved without its merge function: graphs with a `Merge` policy are loaded with `Reject`, use
+/// `with_edge_policy` to set the function again. Input that violates the policy is rejected.
+#[derive(Clone,Debug)]
+pub struct AdjacencyList<N,E,D = DirectedEdges> {
+    vertices:   Slab<VertexEntry<N>>,
+    edges:      Slab<EdgeEntry<E>>,
+    ordered:    bool,
+    direction:  PhantomData<D>,
+    policy:     EdgePolicy<E>,
 }
 
 #[derive(Debug,Serialize,Deserialize)]
@@ -34,74 +144,713 @@ impl Iterator for AdjacencyListAdjacency {
     }
 }
 
+/// Vertices of an `AdjacencyList` in ascending descriptor order.
+#[derive(Debug)]
+pub struct AdjacencyListVertices<'a,N: 'a> {
+    iter: slab::Iter<'a,VertexEntry<N>>
+}
+
+impl<'a,N> Iterator for AdjacencyListVertices<'a,N> {
+    type Item = AdjacencyListVertexDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.iter.next().map(|(idx,generation,_)| AdjacencyListVertexDescriptor(idx,generation));
+    }
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        return self.iter.size_hint();
+    }
+}
+
+impl<'a,N> ExactSizeIterator for AdjacencyListVertices<'a,N> {}
+
+/// Edges of an `AdjacencyList` in ascending descriptor order.
+#[derive(Debug)]
+pub struct AdjacencyListEdges<'a,E: 'a> {
+    iter: slab::Iter<'a,EdgeEntry<E>>
+}
+
+impl<'a,E> Iterator for AdjacencyListEdges<'a,E> {
+    type Item = AdjacencyListEdgeDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.iter.next().map(|(idx,generation,_)| AdjacencyListEdgeDescriptor(idx,generation));
+    }
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        return self.iter.size_hint();
+    }
+}
+
+impl<'a,E> ExactSizeIterator for AdjacencyListEdges<'a,E> {}
+
+/// Edges between two vertices of an `AdjacencyList`.
+#[derive(Debug)]
+pub struct AdjacencyListEdgesBetween<'a,V: 'a,E: 'a,D: 'a> {
+    graph: &'a AdjacencyList<V,E,D>,
+    edges: std::slice::Iter<'a,AdjacencyListEdgeDescriptor>,
+    from: AdjacencyListVertexDescriptor,
+    to: AdjacencyListVertexDescriptor,
+}
+
+impl<'a,V,E,D: Direction> Iterator for AdjacencyListEdgesBetween<'a,V,E,D> {
+    type Item = AdjacencyListEdgeDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (graph,from,to) = (self.graph,self.from,self.to);
+        return self.edges.find(|&&e| graph.try_opposite(e,from) == Ok(to)).cloned();
+    }
+}
+
 impl<V,E> AdjacencyList<V,E> {
     pub fn new() -> Self {
+        return Self::empty(false);
+    }
+
+    /// Creates a graph whose edge removals preserve the order of the out and in edge lists.
+    pub fn with_ordered_edges() -> Self {
+        return Self::empty(true);
+    }
+
+    /// Builds a graph from labeled vertices and `(from, to, label)` edges between them. Vertices
+    /// are identified by user supplied keys, the returned map translates keys to descriptors.
+    /// Fails with `DuplicateKey` if a key is used twice and with `UnknownVertex` if an edge refers
+    /// to an unknown key.
+    pub fn from_edges<K,VI,EI>(vertices: VI, edges: EI) -> Result<(Self,HashMap<K,AdjacencyListVertexDescriptor>),GraphError>
+        where K: Hash + Eq, VI: IntoIterator<Item=(K,V)>, EI: IntoIterator<Item=(K,K,E)> {
+        let mut ret = Self::new();
+        let ids = ret.add_keyed(vertices,edges)?;
+
+        return Ok((ret,ids));
+    }
+}
+
+impl<V,E> AdjacencyList<V,E,UndirectedEdges> {
+    /// Creates an undirected graph.
+    pub fn undirected() -> Self {
+        return Self::empty(false);
+    }
+
+    /// Creates an undirected graph whose edge removals preserve the order of the edge lists.
+    pub fn undirected_with_ordered_edges() -> Self {
+        return Self::empty(true);
+    }
+
+    /// Undirected version of `from_edges`.
+    pub fn undirected_from_edges<K,VI,EI>(vertices: VI, edges: EI) -> Result<(Self,HashMap<K,AdjacencyListVertexDescriptor>),GraphError>
+        where K: Hash + Eq, VI: IntoIterator<Item=(K,V)>, EI: IntoIterator<Item=(K,K,E)> {
+        let mut ret = Self::undirected();
+        let ids = ret.add_keyed(vertices,edges)?;
+
+        return Ok((ret,ids));
+    }
+}
+
+impl<V,E,D: Direction> AdjacencyList<V,E,D> {
+    pub(crate) fn empty(ordered: bool) -> Self {
         return AdjacencyList {
-            vertex_labels: HashMap::new(),
-            edge_labels: HashMap::new(),
-            out_edges: HashMap::new(),
-            in_edges: HashMap::new(),
-            edges: HashMap::new(),
-            next_edge: AdjacencyListEdgeDescriptor(0),
-            next_vertex: AdjacencyListVertexDescriptor(0)
+            vertices: Slab::new(),
+            edges: Slab::new(),
+            ordered: ordered,
+            direction: PhantomData,
+            policy: EdgePolicy::default(),
+        };
+    }
+
+    /// Sets the policy for parallel edges and self-loops. Only edges added afterwards are
+    /// checked against it, see `with_checked_edge_policy`.
+    pub fn with_edge_policy(mut self, policy: EdgePolicy<E>) -> Self {
+        self.policy = policy;
+        return self;
+    }
+
+    /// Sets the policy after checking that the edges of the graph satisfy it. Fails with
+    /// `SelfLoop` or `EdgeExists` otherwise.
+    pub fn with_checked_edge_policy(mut self, policy: EdgePolicy<E>) -> Result<Self,GraphError> {
+        let parallel_edges = matches!(policy.parallel_edges,ParallelEdges::Allow);
+        let mut seen = HashSet::new();
+
+        for (_,_,entry) in self.edges.iter() {
+            if !policy.self_loops && entry.from == entry.to {
+                return Err(GraphError::SelfLoop);
+            }
+
+            let key = if D::DIRECTED || entry.from <= entry.to { (entry.from,entry.to) } else { (entry.to,entry.from) };
+
+            if !seen.insert(key) && !parallel_edges {
+                return Err(GraphError::EdgeExists);
+            }
+        }
+
+        self.policy = policy;
+        return Ok(self);
+    }
+
+    pub fn edge_policy(&self) -> &EdgePolicy<E> {
+        return &self.policy;
+    }
+
+    /// All edges from `from` to `to` in the order of the out edge list of `from`. For undirected
+    /// graphs this includes the edges from `to` to `from`.
+    pub fn edges_between<'a>(&'a self, from: AdjacencyListVertexDescriptor, to: AdjacencyListVertexDescriptor) -> AdjacencyListEdgesBetween<'a,V,E,D> {
+        let edges = self.vertex_entry(from).map(|x| &x.out_edges[..]).unwrap_or(&[]);
+
+        return AdjacencyListEdgesBetween{
+            graph: self,
+            edges: edges.iter(),
+            from: from,
+            to: to,
+        };
+    }
+
+    /// Applies the edge policy to a new edge `lb` from `from` to `to`. Returns the label if the
+    /// edge should be added, the existing edge if it was merged into one.
+    fn check_edge_policy(&mut self, lb: E, from: AdjacencyListVertexDescriptor, to: AdjacencyListVertexDescriptor) -> Result<Result<E,AdjacencyListEdgeDescriptor>,GraphError> {
+        if self.vertex_entry(from).is_none() || self.vertex_entry(to).is_none() {
+            return Err(GraphError::UnknownVertex);
+        }
+
+        if !self.policy.self_loops && from == to {
+            return Err(GraphError::SelfLoop);
+        }
+
+        match self.policy.parallel_edges {
+            ParallelEdges::Allow => return Ok(Ok(lb)),
+            ParallelEdges::Reject => {
+                if self.edges_between(from,to).next().is_some() {
+                    return Err(GraphError::EdgeExists);
+                } else {
+                    return Ok(Ok(lb));
+                }
+            }
+            ParallelEdges::Merge(ref merge) => {
+                match self.edges_between(from,to).next() {
+                    Some(e) => {
+                        merge(&mut self.edges.get_mut(e.0,e.1).unwrap().label,lb);
+                        return Ok(Err(e));
+                    }
+                    None => return Ok(Ok(lb)),
+                }
+            }
+        }
+    }
+
+    /// Makes room for `vertices` more vertices and `edges` more edges.
+    pub fn reserve(&mut self, vertices: usize, edges: usize) {
+        self.vertices.reserve(vertices);
+        self.edges.reserve(edges);
+    }
+
+    /// Graph with the same structure and descriptors whose labels are computed by `vertex_label`
+    /// and `edge_label`. See `filter_map`.
+    pub fn map<V2,E2,FV,FE>(&self, mut vertex_label: FV, mut edge_label: FE) -> AdjacencyList<V2,E2,D>
+        where FV: FnMut(AdjacencyListVertexDescriptor,&V) -> V2, FE: FnMut(AdjacencyListEdgeDescriptor,&E) -> E2 {
+        return self.filter_map(|v,lb| Some(vertex_label(v,lb)),|e,lb| Some(edge_label(e,lb)));
+    }
+
+    /// Graph with the vertices and edges for which `vertex_label` resp. `edge_label` return a new
+    /// label. Edges of removed vertices are removed as well. The remaining vertices and edges keep
+    /// their descriptors and the order of their edge lists, descriptors of removed ones are
+    /// invalid in the new graph. The new graph uses the default edge policy.
+    pub fn filter_map<V2,E2,FV,FE>(&self, mut vertex_label: FV, mut edge_label: FE) -> AdjacencyList<V2,E2,D>
+        where FV: FnMut(AdjacencyListVertexDescriptor,&V) -> Option<V2>, FE: FnMut(AdjacencyListEdgeDescriptor,&E) -> Option<E2> {
+        let mut vertices = self.vertices.filter_map(|idx,generation,entry| {
+            vertex_label(AdjacencyListVertexDescriptor(idx,generation),&entry.label).map(|lb| {
+                VertexEntry{ label: lb, out_edges: vec![], in_edges: vec![] }
+            })
+        });
+        let edges = self.edges.filter_map(|idx,generation,entry| {
+            if vertices.get(entry.from.0,entry.from.1).is_none() || vertices.get(entry.to.0,entry.to.1).is_none() {
+                return None;
+            }
+
+            return edge_label(AdjacencyListEdgeDescriptor(idx,generation),&entry.label).map(|lb| {
+                EdgeEntry{ label: lb, from: entry.from, to: entry.to }
+            });
+        });
+
+        for (idx,generation,entry) in self.vertices.iter() {
+            if let Some(new) = vertices.get_mut(idx,generation) {
+                new.out_edges = entry.out_edges.iter().cloned().filter(|e| edges.get(e.0,e.1).is_some()).collect();
+                new.in_edges = entry.in_edges.iter().cloned().filter(|e| edges.get(e.0,e.1).is_some()).collect();
+            }
+        }
+
+        return AdjacencyList{
+            vertices: vertices,
+            edges: edges,
+            ordered: self.ordered,
+            direction: PhantomData,
+            policy: EdgePolicy::default(),
+        };
+    }
+
+    /// Graph with the given vertices and `(descriptor, from, to, label)` edges under exactly these
+    /// descriptors and the free slots in `free`. Edge lists are in the order of `edges`, the edge
+    /// policy is not applied. Fails with `Inconsistent` unless the descriptors and free slots
+    /// cover each index below their number exactly once, and with `UnknownVertex` if an edge
+    /// refers to a vertex not in `vertices`.
+    pub(crate) fn with_descriptors(vertices: Vec<(AdjacencyListVertexDescriptor,V)>, edges: Vec<(AdjacencyListEdgeDescriptor,AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor,E)>, free: FreeSlots, ordered: bool) -> Result<Self,GraphError> {
+        let vertices = vertices.into_iter().map(|(v,lb)| {
+            (v.0,v.1,VertexEntry{ label: lb, out_edges: vec![], in_edges: vec![] })
+        });
+        let mut ret = Self::empty(ordered);
+
+        ret.vertices = Slab::from_entries(vertices,free.vertices,free.vertex_generation).ok_or(GraphError::Inconsistent)?;
+
+        let mut entries = Vec::with_capacity(edges.len());
+
+        for (e,from,to,lb) in edges {
+            if ret.vertex_entry(from).is_none() || ret.vertex_entry(to).is_none() {
+                return Err(GraphError::UnknownVertex);
+            }
+
+            ret.vertex_entry_mut(from).unwrap().out_edges.push(e);
+            ret.vertex_entry_mut(to).unwrap().in_edges.push(e);
+
+            if !D::DIRECTED && from != to {
+                ret.vertex_entry_mut(to).unwrap().out_edges.push(e);
+                ret.vertex_entry_mut(from).unwrap().in_edges.push(e);
+            }
+
+            entries.push((e.0,e.1,EdgeEntry{ label: lb, from: from, to: to }));
+        }
+
+        ret.edges = Slab::from_entries(entries,free.edges,free.edge_generation).ok_or(GraphError::Inconsistent)?;
+        return Ok(ret);
+    }
+
+    /// Merges `b` into `a`. The edges of `b` are moved to `a` and keep their descriptors, edges
+    /// between `a` and `b` become self-loops of `a`. If `drop_loops` is set these self-loops and
+    /// those of `b` are removed. `merge_labels` combines the label of `b` into the one of `a`.
+    ///
+    /// The moved edges are subject to the edge policy: self-loops are removed if the policy
+    /// forbids them and edges parallel to an existing one are merged into it or, with
+    /// `ParallelEdges::Reject`, make the call fail with `EdgeExists` without changing the graph.
+    pub fn merge_vertices<F: FnOnce(&mut V,V)>(&mut self, a: AdjacencyListVertexDescriptor, b: AdjacencyListVertexDescriptor, merge_labels: F, drop_loops: bool) -> Result<(),GraphError> {
+        return self.merge_into(a,b,merge_labels,drop_loops,None);
+    }
+
+    /// Removes `e` and merges its target into its source using `merge_vertices`. Returns the
+    /// remaining vertex. Self-loops can not be contracted.
+    pub fn contract_edge<F: FnOnce(&mut V,V)>(&mut self, e: AdjacencyListEdgeDescriptor, merge_labels: F, drop_loops: bool) -> Result<AdjacencyListVertexDescriptor,GraphError> {
+        let (from,to) = match self.edge_entry(e) {
+            Some(entry) if entry.from == entry.to => return Err(GraphError::SelfLoop),
+            Some(entry) => (entry.from,entry.to),
+            None => return Err(GraphError::UnknownEdge),
+        };
+
+        self.merge_into(from,to,merge_labels,drop_loops,Some(e))?;
+        return Ok(from);
+    }
+
+    /// `merge_vertices` that removes `contracted` before moving the edges.
+    fn merge_into<F: FnOnce(&mut V,V)>(&mut self, a: AdjacencyListVertexDescriptor, b: AdjacencyListVertexDescriptor, merge_labels: F, drop_loops: bool, contracted: Option<AdjacencyListEdgeDescriptor>) -> Result<(),GraphError> {
+        if self.vertex_entry(a).is_none() || self.vertex_entry(b).is_none() {
+            return Err(GraphError::UnknownVertex);
+        }
+
+        if a == b {
+            return Ok(());
+        }
+
+        let incident = |entry: &VertexEntry<V>| {
+            let mut ret = entry.out_edges.iter().chain(entry.in_edges.iter()).cloned().filter(|&e| Some(e) != contracted).collect::<Vec<_>>();
+
+            ret.sort();
+            ret.dedup();
+            ret
         };
+        let moved = incident(self.vertex_entry(b).unwrap());
+        let key = |from: AdjacencyListVertexDescriptor,to: AdjacencyListVertexDescriptor| {
+            if D::DIRECTED || from <= to { (from,to) } else { (to,from) }
+        };
+
+        // decide the fate of every moved edge before touching the graph
+        let mut dropped = vec![];
+        let mut merged = vec![];
+        let mut existing = HashMap::new();
+
+        if !matches!(self.policy.parallel_edges,ParallelEdges::Allow) {
+            for e in incident(self.vertex_entry(a).unwrap()) {
+                let entry = self.edge_entry(e).unwrap();
+
+                if entry.from != b && entry.to != b {
+                    existing.entry(key(entry.from,entry.to)).or_insert(e);
+                }
+            }
+        }
+
+        for &e in moved.iter() {
+            let entry = self.edge_entry(e).unwrap();
+            let from = if entry.from == b { a } else { entry.from };
+            let to = if entry.to == b { a } else { entry.to };
+
+            if from == to && (drop_loops || !self.policy.self_loops) {
+                dropped.push(e);
+                continue;
+            }
+
+            match self.policy.parallel_edges {
+                ParallelEdges::Allow => {}
+                ParallelEdges::Reject => {
+                    if existing.insert(key(from,to),e).is_some() {
+                        return Err(GraphError::EdgeExists);
+                    }
+                }
+                ParallelEdges::Merge(_) => {
+                    match existing.entry(key(from,to)) {
+                        Entry::Occupied(into) => merged.push((e,*into.get())),
+                        Entry::Vacant(slot) => { slot.insert(e); }
+                    }
+                }
+            }
+        }
+
+        if let Some(e) = contracted {
+            self.remove_edge(e).ok_or(GraphError::Inconsistent)?;
+        }
+
+        let entry = self.vertices.remove(b.0,b.1).unwrap();
+
+        for &e in moved.iter() {
+            let edge = self.edges.get_mut(e.0,e.1).unwrap();
+
+            if edge.from == b { edge.from = a; }
+            if edge.to == b { edge.to = a; }
+        }
+
+        {
+            let survivor = self.vertices.get_mut(a.0,a.1).unwrap();
+            let known_out = survivor.out_edges.iter().cloned().collect::<HashSet<_>>();
+            let known_in = survivor.in_edges.iter().cloned().collect::<HashSet<_>>();
+
+            // undirected edges between a and b are in the lists of both
+            survivor.out_edges.extend(entry.out_edges.into_iter().filter(|e| !known_out.contains(e)));
+            survivor.in_edges.extend(entry.in_edges.into_iter().filter(|e| !known_in.contains(e)));
+            merge_labels(&mut survivor.label,entry.label);
+        }
+
+        for e in dropped {
+            self.remove_edge(e);
+        }
+
+        if let ParallelEdges::Merge(merge) = self.policy.parallel_edges.clone() {
+            for (e,into) in merged {
+                let lb = self.remove_edge(e).unwrap();
+                merge(&mut self.edges.get_mut(into.0,into.1).unwrap().label,lb);
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Renumbers vertices and edges densely from zero, removing the holes left by removals.
+    /// Relative order is kept. Returns maps from the old to the new descriptors. All descriptors
+    /// from before the call are invalid afterwards, they never refer to a different element.
+    pub fn compact(&mut self) -> (HashMap<AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor>,HashMap<AdjacencyListEdgeDescriptor,AdjacencyListEdgeDescriptor>) {
+        let old_vertices = self.vertices.iter().map(|(idx,generation,_)| AdjacencyListVertexDescriptor(idx,generation)).collect::<Vec<_>>();
+        let old_edges = self.edges.iter().map(|(idx,generation,_)| AdjacencyListEdgeDescriptor(idx,generation)).collect::<Vec<_>>();
+        let vertex_remap = self.vertices.compact();
+        let edge_remap = self.edges.compact();
+        let vertex_map = old_vertices.into_iter().map(|v| {
+            let (idx,generation) = vertex_remap[v.0].unwrap();
+            (v,AdjacencyListVertexDescriptor(idx,generation))
+        }).collect::<HashMap<_,_>>();
+        let edge_map = old_edges.into_iter().map(|e| {
+            let (idx,generation) = edge_remap[e.0].unwrap();
+            (e,AdjacencyListEdgeDescriptor(idx,generation))
+        }).collect::<HashMap<_,_>>();
+
+        for v in vertex_map.values() {
+            let entry = self.vertices.get_mut(v.0,v.1).unwrap();
+
+            for e in entry.out_edges.iter_mut().chain(entry.in_edges.iter_mut()) {
+                *e = edge_map[e];
+            }
+        }
+
+        for e in edge_map.values() {
+            let entry = self.edges.get_mut(e.0,e.1).unwrap();
+
+            entry.from = vertex_map[&entry.from];
+            entry.to = vertex_map[&entry.to];
+        }
+
+        return (vertex_map,edge_map);
+    }
+
+    /// Adds `(from, to, label)` edges between existing vertices, reserving room for all of them
+    /// first. Stops at the first edge that refers to an unknown vertex or is rejected by the edge
+    /// policy and returns its error, the edges before it stay in the graph.
+    pub fn try_extend<I: IntoIterator<Item=(AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor,E)>>(&mut self, edges: I) -> Result<(),GraphError> {
+        let edges = edges.into_iter();
+
+        self.reserve(0,edges.size_hint().0);
+
+        for (from,to,lb) in edges {
+            self.try_add_edge(lb,from,to)?;
+        }
+
+        return Ok(());
+    }
+
+    fn add_keyed<K,VI,EI>(&mut self, vertices: VI, edges: EI) -> Result<HashMap<K,AdjacencyListVertexDescriptor>,GraphError>
+        where K: Hash + Eq, VI: IntoIterator<Item=(K,V)>, EI: IntoIterator<Item=(K,K,E)> {
+        let vertices = vertices.into_iter();
+        let edges = edges.into_iter();
+        let mut ids = HashMap::with_capacity(vertices.size_hint().0);
+
+        self.reserve(vertices.size_hint().0,edges.size_hint().0);
+
+        for (k,lb) in vertices {
+            match ids.entry(k) {
+                Entry::Vacant(entry) => { entry.insert(self.add_vertex(lb)); }
+                Entry::Occupied(_) => return Err(GraphError::DuplicateKey),
+            }
+        }
+
+        for (from,to,lb) in edges {
+            match (ids.get(&from),ids.get(&to)) {
+                (Some(&from),Some(&to)) => { self.try_add_edge(lb,from,to)?; }
+                _ => return Err(GraphError::UnknownVertex),
+            }
+        }
+
+        return Ok(ids);
+    }
+
+    /// True if edge removals preserve the order of the edge lists.
+    pub fn has_ordered_edges(&self) -> bool {
+        return self.ordered;
+    }
+
+    /// True unless the graph is an `AdjacencyList<V,E,UndirectedEdges>`.
+    pub fn is_directed(&self) -> bool {
+        return D::DIRECTED;
+    }
+
+    /// Adds an edge and inserts it at position `pos` of the out edge list of `from`. The edge is
+    /// appended to all other edge lists it belongs to.
+    pub fn insert_edge_at(&mut self, lb: E, from: AdjacencyListVertexDescriptor, to: AdjacencyListVertexDescriptor, pos: usize) -> Result<AdjacencyListEdgeDescriptor,GraphError> {
+        if self.vertex_entry(from).is_some_and(|x| pos > x.out_edges.len()) {
+            return Err(GraphError::InvalidPosition);
+        }
+
+        let lb = match self.check_edge_policy(lb,from,to)? {
+            Ok(lb) => lb,
+            Err(e) => return Ok(e),
+        };
+
+        let (idx,generation) = self.edges.insert(EdgeEntry{ label: lb, from: from, to: to });
+        let e = AdjacencyListEdgeDescriptor(idx,generation);
+
+        self.vertex_entry_mut(from).unwrap().out_edges.insert(pos,e);
+        self.vertex_entry_mut(to).unwrap().in_edges.push(e);
+
+        if !D::DIRECTED && from != to {
+            self.vertex_entry_mut(to).unwrap().out_edges.push(e);
+            self.vertex_entry_mut(from).unwrap().in_edges.push(e);
+        }
+
+        return Ok(e);
+    }
+
+    /// Replaces the out edge list of `v` with `order`, which must be a permutation of it.
+    pub fn reorder_out_edges(&mut self, v: AdjacencyListVertexDescriptor, order: &[AdjacencyListEdgeDescriptor]) -> Result<(),GraphError> {
+        let entry = self.vertices.get_mut(v.0,v.1).ok_or(GraphError::UnknownVertex)?;
+        let mut cur = entry.out_edges.clone();
+        let mut new = order.to_vec();
+
+        cur.sort();
+        new.sort();
+
+        if cur != new {
+            return Err(GraphError::InvalidOrder);
+        }
+
+        entry.out_edges = order.to_vec();
+        return Ok(());
+    }
+
+    fn vertex_entry(&self, v: AdjacencyListVertexDescriptor) -> Option<&VertexEntry<V>> {
+        return self.vertices.get(v.0,v.1);
+    }
+
+    fn vertex_entry_mut(&mut self, v: AdjacencyListVertexDescriptor) -> Option<&mut VertexEntry<V>> {
+        return self.vertices.get_mut(v.0,v.1);
+    }
+
+    fn edge_entry(&self, e: AdjacencyListEdgeDescriptor) -> Option<&EdgeEntry<E>> {
+        return self.edges.get(e.0,e.1);
+    }
+
+    /// `opposite` that fails with `UnknownEdge` instead of panicking.
+    fn try_opposite(&self, e: AdjacencyListEdgeDescriptor, v: AdjacencyListVertexDescriptor) -> Result<AdjacencyListVertexDescriptor,GraphError> {
+        let s = self.try_source(e)?;
+
+        if s == v {
+            return self.try_target(e);
+        } else {
+            return Ok(s);
+        }
+    }
+
+    /// Edge lists `e` is part of as (vertex, out list?) pairs. Undirected edges are in the out and
+    /// in list of both endpoints.
+    fn edge_lists(&self, e: AdjacencyListEdgeDescriptor) -> Option<Vec<(AdjacencyListVertexDescriptor,bool)>> {
+        let entry = self.edge_entry(e)?;
+        let mut ret = vec![(entry.from,true),(entry.to,false)];
+
+        if !D::DIRECTED && entry.from != entry.to {
+            ret.push((entry.to,true));
+            ret.push((entry.from,false));
+        }
+
+        return Some(ret);
+    }
+
+    /// Edge lists containing `e` with the position of `e` in them. `None` if `e` is missing from
+    /// any list it should be in.
+    fn edge_positions(&self, e: AdjacencyListEdgeDescriptor) -> Option<Vec<(AdjacencyListVertexDescriptor,bool,usize)>> {
+        let mut ret = vec![];
+
+        for (v,out) in self.edge_lists(e)? {
+            let entry = self.vertex_entry(v)?;
+            let list = if out { &entry.out_edges } else { &entry.in_edges };
+
+            ret.push((v,out,list.iter().position(|&x| x == e)?));
+        }
+
+        return Some(ret);
+    }
+
+    /// Checks that the edge table and the per-vertex in and out edge lists agree: every edge
+    /// connects existing vertices and appears exactly once in the out list of its source and the
+    /// in list of its target (of both endpoints if undirected), and the lists contain nothing
+    /// else.
+    pub fn check_invariants(&self) -> Result<(),GraphError> {
+        let mut num_lists = 0;
+        let mut num_entries = 0;
+
+        for (idx,generation,entry) in self.vertices.iter() {
+            let v = AdjacencyListVertexDescriptor(idx,generation);
+
+            for &e in entry.out_edges.iter() {
+                match self.edge_entry(e) {
+                    Some(x) if x.from == v || (!D::DIRECTED && x.to == v) => {}
+                    _ => return Err(GraphError::Inconsistent),
+                }
+            }
+
+            for &e in entry.in_edges.iter() {
+                match self.edge_entry(e) {
+                    Some(x) if x.to == v || (!D::DIRECTED && x.from == v) => {}
+                    _ => return Err(GraphError::Inconsistent),
+                }
+            }
+
+            num_entries += entry.out_edges.len() + entry.in_edges.len();
+        }
+
+        for (idx,generation,_) in self.edges.iter() {
+            let e = AdjacencyListEdgeDescriptor(idx,generation);
+
+            for (v,out) in self.edge_lists(e).unwrap() {
+                let entry = self.vertex_entry(v).ok_or(GraphError::Inconsistent)?;
+                let list = if out { &entry.out_edges } else { &entry.in_edges };
+
+                if list.iter().filter(|&&x| x == e).count() != 1 {
+                    return Err(GraphError::Inconsistent);
+                }
+
+                num_lists += 1;
+            }
+        }
+
+        if num_lists != num_entries {
+            return Err(GraphError::Inconsistent);
+        }
+
+        return Ok(());
     }
 }
 
-impl<'a,V,E> Graph<'a,V,E> for AdjacencyList<V,E> {
+impl<'a,V,E,D: Direction> Graph<'a,V,E> for AdjacencyList<V,E,D> {
     type Vertex = AdjacencyListVertexDescriptor;
     type Edge = AdjacencyListEdgeDescriptor;
 
     #[inline]
     fn vertex_label(&self, n: Self::Vertex) -> Option<&V> {
-        return self.vertex_labels.get(&n);
+        return self.vertex_entry(n).map(|x| &x.label);
     }
 
     fn edge_label(&self, n: Self::Edge) -> Option<&E> {
-        return self.edge_labels.get(&n);
+        return self.edge_entry(n).map(|x| &x.label);
     }
 
+    /// Panics if `e` is not in the graph, see `try_source`.
     fn source(&self, e: Self::Edge) -> Self::Vertex {
-        return self.edges.get(&e).unwrap().0;
+        return self.edge_entry(e).expect("unknown edge").from;
     }
 
+    /// Panics if `e` is not in the graph, see `try_target`.
     fn target(&self, e: Self::Edge) -> Self::Vertex {
-        return self.edges.get(&e).unwrap().1;
+        return self.edge_entry(e).expect("unknown edge").to;
+    }
+
+    fn try_source(&self, e: Self::Edge) -> Result<Self::Vertex,GraphError> {
+        return self.edge_entry(e).map(|x| x.from).ok_or(GraphError::UnknownEdge);
+    }
+
+    fn try_target(&self, e: Self::Edge) -> Result<Self::Vertex,GraphError> {
+        return self.edge_entry(e).map(|x| x.to).ok_or(GraphError::UnknownEdge);
     }
 }
 
-impl<'a,V,E> IncidenceGraph<'a,V,E> for AdjacencyList<V,E> {
+impl<'a,V,E,D: Direction> IncidenceGraph<'a,V,E> for AdjacencyList<V,E,D> {
     type Incidence = std::iter::Map<std::slice::Iter<'a, Self::Edge>,fn(&Self::Edge) -> Self::Edge>;
 
     fn out_degree(&self, v: Self::Vertex) -> usize {
-        return self.out_edges.get(&v).map_or(0,|ref x| return x.len());
+        return self.vertex_entry(v).map_or(0,|x| return x.out_edges.len());
     }
 
+    /// Empty for vertices not in the graph.
     fn out_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
-        return self.out_edges.get(&v).unwrap().iter().map(std::clone::Clone::clone);
+        let edges = self.vertex_entry(v).map(|x| &x.out_edges[..]).unwrap_or(&[]);
+        return edges.iter().map(std::clone::Clone::clone);
     }
 }
 
-impl<'a,V,E> BidirectionalGraph<'a,V,E> for AdjacencyList<V,E> {
+impl<'a,V,E,D: Direction> BidirectionalGraph<'a,V,E> for AdjacencyList<V,E,D> {
    fn in_degree(&self, v: Self::Vertex) -> usize {
-        return self.in_edges.get(&v).map_or(0,|ref x| return x.len());
+        return self.vertex_entry(v).map_or(0,|x| return x.in_edges.len());
     }
 
     fn degree(&self, v: Self::Vertex) -> usize {
-        return self.in_degree(v) + self.out_degree(v);
+        if D::DIRECTED {
+            return self.in_degree(v) + self.out_degree(v);
+        } else {
+            // self-loops are listed once but count twice
+            let loops = self.vertex_entry(v).map_or(0,|x| x.out_edges.iter().filter(|&&e| self.try_opposite(e,v) == Ok(v)).count());
+            return self.out_degree(v) + loops;
+        }
     }
 
+    /// Empty for vertices not in the graph.
     fn in_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
-        return self.in_edges.get(&v).unwrap().iter().map(std::clone::Clone::clone);
+        let edges = self.vertex_entry(v).map(|x| &x.in_edges[..]).unwrap_or(&[]);
+        return edges.iter().map(std::clone::Clone::clone);
     }
 }
 
-impl<'a,V,E> AdjacencyGraph<'a,V,E> for AdjacencyList<V,E> {
+impl<'a,V,E> UndirectedGraph<'a,V,E> for AdjacencyList<V,E,UndirectedEdges> {}
+
+impl<'a,V,E,D: Direction> AdjacencyGraph<'a,V,E> for AdjacencyList<V,E,D> {
     type Adjacency = AdjacencyListAdjacency;
 
+    /// Empty for vertices not in the graph.
     fn adjacent_vertices(&self, v: Self::Vertex) -> Self::Adjacency {
-        let i = self.out_edges.get(&v).unwrap().iter().map(|&x| return self.target(x));
-        let o = self.in_edges.get(&v).unwrap().iter().map(|&x| return self.source(x));
+        let i = self.out_edges(v).filter_map(|x| return self.try_opposite(x,v).ok());
+        let o = self.in_edges(v).filter_map(|x| return self.try_opposite(x,v).ok());
         let mut raw = i.chain(o).collect::<Vec<AdjacencyListVertexDescriptor>>();
 
         raw.sort();
@@ -111,155 +860,307 @@ impl<'a,V,E> AdjacencyGraph<'a,V,E> for AdjacencyList<V,E> {
     }
 }
 
-impl<'a,V: 'a,E> VertexListGraph<'a,V,E> for AdjacencyList<V,E> {
-    type Vertices = std::iter::Map<std::collections::hash_map::Keys<'a, Self::Vertex, V>,fn(&Self::Vertex) -> Self::Vertex>;
+impl<'a,V: 'a,E,D: Direction> VertexListGraph<'a,V,E> for AdjacencyList<V,E,D> {
+    type Vertices = AdjacencyListVertices<'a,V>;
 
     fn num_vertices(&self) -> usize {
-        return self.vertex_labels.len();
+        return self.vertices.len();
     }
 
     fn vertices(&'a self) -> Self::Vertices {
-        return self.vertex_labels.keys().map(std::clone::Clone::clone);
+        return AdjacencyListVertices{ iter: self.vertices.iter() };
     }
 }
 
-impl<'a,V,E: 'a> EdgeListGraph<'a,V,E> for AdjacencyList<V,E> {
-    type Edges = std::iter::Map<std::collections::hash_map::Keys<'a, Self::Edge, E>,fn(&Self::Edge) -> Self::Edge>;
+impl<'a,V,E: 'a,D: Direction> EdgeListGraph<'a,V,E> for AdjacencyList<V,E,D> {
+    type Edges = AdjacencyListEdges<'a,E>;
 
     fn num_edges(&self) -> usize {
-        return self.edge_labels.len();
+        return self.edges.len();
     }
 
     fn edges(&'a self) -> Self::Edges {
-        return self.edge_labels.keys().map(std::clone::Clone::clone);
+        return AdjacencyListEdges{ iter: self.edges.iter() };
     }
 }
 
 
-impl<'a,V,E> AdjacencyMatrixGraph<'a,V,E> for AdjacencyList<V,E> {
+impl<'a,V,E,D: Direction> AdjacencyMatrixGraph<'a,V,E> for AdjacencyList<V,E,D> {
     fn edge(&'a self,from: Self::Vertex,to: Self::Vertex) -> Option<Self::Edge> {
-        match self.out_edges(from).find(|&e| self.target(e) == to) {
+        match self.out_edges(from).find(|&e| self.try_opposite(e,from) == Ok(to)) {
             Some(e) => Some(e.clone()),
             None => None
         }
     }
 }
 
-impl<'a,V,E> MutableGraph<'a,V,E> for AdjacencyList<V,E> {
-    fn add_vertex(&mut self, lb: V) -> Self::Vertex {
-        let n = self.next_vertex;
+impl<'a,V,E,D: Direction> VertexIndexGraph<'a,V,E> for AdjacencyList<V,E,D> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return v.0;
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.vertices.bound();
+    }
+}
+
+impl<'a,V,E,D: Direction> EdgeIndexGraph<'a,V,E> for AdjacencyList<V,E,D> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return e.0;
+    }
 
-        self.next_vertex.0 += 1;
-        self.vertex_labels.insert(n,lb);
-        self.out_edges.insert(n,Vec::new());
-        self.in_edges.insert(n,Vec::new());
+    fn edge_index_bound(&self) -> usize {
+        return self.edges.bound();
+    }
+}
+
+impl<'a,V,E,D: Direction> MutableGraph<'a,V,E> for AdjacencyList<V,E,D> {
+    fn add_vertex(&mut self, lb: V) -> Self::Vertex {
+        let (idx,generation) = self.vertices.insert(VertexEntry{
+            label: lb,
+            out_edges: Vec::new(),
+            in_edges: Vec::new(),
+        });
 
-        return n;
+        return AdjacencyListVertexDescriptor(idx,generation);
     }
 
     fn add_edge(&mut self, lb: E, from: Self::Vertex, to: Self::Vertex) -> Option<Self::Edge> {
-        if self.vertex_labels.contains_key(&from) && self.vertex_labels.contains_key(&to) {
-            let e = self.next_edge;
+        return self.try_add_edge(lb,from,to).ok();
+    }
 
-            self.next_edge.0 += 1;
-            self.edge_labels.insert(e,lb);
-            self.edges.insert(e,(from,to));
+    fn try_add_edge(&mut self, lb: E, from: Self::Vertex, to: Self::Vertex) -> Result<Self::Edge,GraphError> {
+        let lb = match self.check_edge_policy(lb,from,to)? {
+            Ok(lb) => lb,
+            Err(e) => return Ok(e),
+        };
+        let (idx,generation) = self.edges.insert(EdgeEntry{ label: lb, from: from, to: to });
+        let e = AdjacencyListEdgeDescriptor(idx,generation);
 
-            let ie = self.in_edges.get_mut(&to);
-            let oe = self.out_edges.get_mut(&from);
+        self.vertex_entry_mut(from).unwrap().out_edges.push(e);
+        self.vertex_entry_mut(to).unwrap().in_edges.push(e);
 
-            if ie.is_some() && oe.is_some() {
-                ie.unwrap().push(e);
-                oe.unwrap().push(e);
-                return Some(e);
-            } else {
-                return None;
-            }
-        } else {
-            return None;
+        if !D::DIRECTED && from != to {
+            self.vertex_entry_mut(to).unwrap().out_edges.push(e);
+            self.vertex_entry_mut(from).unwrap().in_edges.push(e);
         }
+
+        return Ok(e);
     }
 
     fn remove_vertex(&mut self, v: Self::Vertex) -> Option<V> {
-        let ret = self.vertex_labels.remove(&v);
+        let mut todel = match self.vertex_entry(v) {
+            Some(entry) => entry.in_edges.iter().chain(entry.out_edges.iter()).cloned().collect::<Vec<_>>(),
+            None => return None,
+        };
 
-        if ret.is_some() {
-            let todel1 = match self.in_edges.get(&v) {
-                Some(_v) => _v.iter().map(|&x| x.clone()).collect(),
-                None => Vec::new()
-            };
+        // self loops are in both lists
+        todel.sort();
+        todel.dedup();
 
-            let todel2 = match self.out_edges.get(&v) {
-                Some(_v) => _v.iter().map(|&x| x.clone()).collect(),
-                None => Vec::new()
-            };
+        // check everything before touching the graph so that we never leave it half-mutated
+        if todel.iter().any(|&e| self.edge_positions(e).is_none()) {
+            return None;
+        }
 
-            for e in todel1.iter() {
-                if !self.remove_edge(*e).is_some() {
-                    return None;
-                }
-            }
+        for e in todel {
+            self.remove_edge(e)?;
+        }
 
-            for e in todel2.iter() {
-                if !self.remove_edge(*e).is_some() {
-                    return None;
-                }
-            }
+        return self.vertices.remove(v.0,v.1).map(|x| x.label);
+    }
+
+    fn remove_edge(&mut self, e: Self::Edge) -> Option<E> {
+        let ordered = self.ordered;
+
+        for (v,out,pos) in self.edge_positions(e)? {
+            let entry = self.vertex_entry_mut(v).unwrap();
+            let list = if out { &mut entry.out_edges } else { &mut entry.in_edges };
 
-            if self.out_edges.remove(&v).is_some() &&
-               self.in_edges.remove(&v).is_some() {
-                    return ret;
+            if ordered {
+                list.remove(pos);
+            } else {
+                list.swap_remove(pos);
             }
         }
 
-        return None;
+        return self.edges.remove(e.0,e.1).map(|x| x.label);
     }
 
-    fn remove_edge(&mut self, e: Self::Edge) -> Option<E> {
-        let ret = self.edge_labels.remove(&e);
+    fn vertex_label_mut(&mut self, n: Self::Vertex) -> Option<&mut V> {
+        return self.vertex_entry_mut(n).map(|x| &mut x.label);
+    }
 
-        if ret.is_some() {
-            let from = &self.source(e);
-            let to = &self.target(e);
+    fn edge_label_mut(&mut self, n: Self::Edge) -> Option<&mut E> {
+        return self.edges.get_mut(n.0,n.1).map(|x| &mut x.label);
+    }
+}
 
-            if !self.edges.remove(&e).is_some() {
-                return None;
-            }
+/// Adds `(from, to, label)` edges between existing vertices. Edges that refer to an unknown
+/// vertex or are rejected by the edge policy are skipped, use `try_extend` to detect them.
+impl<V,E,D: Direction> Extend<(AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor,E)> for AdjacencyList<V,E,D> {
+    fn extend<I: IntoIterator<Item=(AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor,E)>>(&mut self, iter: I) {
+        let edges = iter.into_iter();
 
-            let rm_adj = |cont: Option<&mut Vec<AdjacencyListEdgeDescriptor>>,e| -> bool {
-                match cont {
-                    None => return false,
-                    Some(cont) => {
-                        let o = cont.iter().position(|&x| x == e);
-
-                        if o.is_some() {
-                            if cont.swap_remove(o.unwrap()) != e {
-                                return false;
-                            } else {
-                                return true;
-                            }
-                        } else {
-                            return false;
-                        }
-                    }
-                }
+        self.reserve(0,edges.size_hint().0);
+
+        for (from,to,lb) in edges {
+            let _ = self.try_add_edge(lb,from,to);
+        }
+    }
+}
+
+/// Builds a graph from `(from, to, label)` edges. Vertices are labeled with the keys used in the
+/// edges, one vertex per distinct key.
+impl<V: Hash + Eq + Clone,E,D: Direction> FromIterator<(V,V,E)> for AdjacencyList<V,E,D> {
+    fn from_iter<I: IntoIterator<Item=(V,V,E)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut ret = Self::empty(false);
+        let mut ids = HashMap::<V,AdjacencyListVertexDescriptor>::new();
+
+        ret.reserve(0,iter.size_hint().0);
+
+        for (from,to,lb) in iter {
+            let from = match ids.get(&from) {
+                Some(&v) => v,
+                None => { let v = ret.add_vertex(from.clone()); ids.insert(from,v); v }
+            };
+            let to = match ids.get(&to) {
+                Some(&v) => v,
+                None => { let v = ret.add_vertex(to.clone()); ids.insert(to,v); v }
             };
 
-            if rm_adj(self.out_edges.get_mut(&from),e) &&
-               rm_adj(self.in_edges.get_mut(&to),e) {
-                return ret;
-            }
+            // the default policy accepts every edge
+            ret.add_edge(lb,from,to);
         }
 
-        return None;
+        return ret;
     }
+}
 
-    fn vertex_label_mut(&mut self, n: Self::Vertex) -> Option<&mut V> {
-        return self.vertex_labels.get_mut(&n);
+/// Version of the serialized form written by `AdjacencyList`.
+const FORMAT_VERSION: u32 = 1;
+
+/// Unoccupied vertex and edge slots of an `AdjacencyList`. Restoring them keeps descriptors of
+/// removed elements invalid.
+#[derive(Default,Serialize,Deserialize)]
+pub(crate) struct FreeSlots {
+    /// `(index, generation)` of the free vertex slots, the last one is reused first.
+    pub vertices: Vec<(usize,u32)>,
+    pub edges: Vec<(usize,u32)>,
+    /// Generation of vertices resp. edges in slots past the end.
+    pub vertex_generation: u32,
+    pub edge_generation: u32,
+}
+
+/// `ParallelEdges` without the merge function.
+#[derive(Clone,Copy,PartialEq,Serialize,Deserialize)]
+enum SerializedParallelEdges {
+    Allow,
+    Reject,
+    Merge,
+}
+
+#[derive(Serialize,Deserialize)]
+struct SerializedPolicy {
+    parallel_edges: SerializedParallelEdges,
+    self_loops: bool,
+}
+
+#[derive(Serialize)]
+struct SerializedGraphRef<'a,V: 'a,E: 'a> {
+    version: u32,
+    directed: bool,
+    ordered: bool,
+    policy: SerializedPolicy,
+    vertices: Vec<(AdjacencyListVertexDescriptor,&'a V)>,
+    edges: Vec<(AdjacencyListEdgeDescriptor,AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor,&'a E)>,
+    free: FreeSlots,
+}
+
+#[derive(Deserialize)]
+struct SerializedGraph<V,E> {
+    version: u32,
+    directed: bool,
+    ordered: bool,
+    policy: SerializedPolicy,
+    vertices: Vec<(AdjacencyListVertexDescriptor,V)>,
+    edges: Vec<(AdjacencyListEdgeDescriptor,AdjacencyListVertexDescriptor,AdjacencyListVertexDescriptor,E)>,
+    free: FreeSlots,
+}
+
+impl<V: Serialize,E: Serialize,D: Direction> Serialize for AdjacencyList<V,E,D> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok,S::Error> {
+        let vertices = self.vertices.iter().map(|(idx,generation,entry)| {
+            (AdjacencyListVertexDescriptor(idx,generation),&entry.label)
+        }).collect::<Vec<_>>();
+        let mut edges = Vec::with_capacity(self.edges.len());
+
+        // edges grouped by source in out edge order, so that loading restores the out edge lists
+        for (idx,generation,entry) in self.vertices.iter() {
+            let v = AdjacencyListVertexDescriptor(idx,generation);
+
+            for &e in entry.out_edges.iter() {
+                let edge = self.edge_entry(e).unwrap();
+
+                if edge.from == v {
+                    edges.push((e,edge.from,edge.to,&edge.label));
+                }
+            }
+        }
+
+        let parallel_edges = match self.policy.parallel_edges {
+            ParallelEdges::Allow => SerializedParallelEdges::Allow,
+            ParallelEdges::Reject => SerializedParallelEdges::Reject,
+            ParallelEdges::Merge(_) => SerializedParallelEdges::Merge,
+        };
+        let graph = SerializedGraphRef{
+            version: FORMAT_VERSION,
+            directed: D::DIRECTED,
+            ordered: self.ordered,
+            policy: SerializedPolicy{ parallel_edges: parallel_edges, self_loops: self.policy.self_loops },
+            vertices: vertices,
+            edges: edges,
+            free: FreeSlots{
+                vertices: self.vertices.free_slots(),
+                edges: self.edges.free_slots(),
+                vertex_generation: self.vertices.base(),
+                edge_generation: self.edges.base(),
+            },
+        };
+
+        return graph.serialize(serializer);
     }
+}
 
-    fn edge_label_mut(&mut self, n: Self::Edge) -> Option<&mut E> {
-        return self.edge_labels.get_mut(&n);
+impl<'de,V: Deserialize<'de>,E: Deserialize<'de>,D: Direction> Deserialize<'de> for AdjacencyList<V,E,D> {
+    fn deserialize<De: Deserializer<'de>>(deserializer: De) -> Result<Self,De::Error> {
+        let graph = SerializedGraph::<V,E>::deserialize(deserializer)?;
+
+        if graph.version != FORMAT_VERSION {
+            return Err(de::Error::custom(format!("unsupported format version {}",graph.version)));
+        }
+
+        if graph.directed != D::DIRECTED {
+            let what = if D::DIRECTED { "a directed" } else { "an undirected" };
+            return Err(de::Error::custom(format!("expected {} graph",what)));
+        }
+
+        // the merge function can't be stored
+        let policy = EdgePolicy{
+            parallel_edges: match graph.policy.parallel_edges {
+                SerializedParallelEdges::Allow => ParallelEdges::Allow,
+                SerializedParallelEdges::Reject => ParallelEdges::Reject,
+                SerializedParallelEdges::Merge => return Err(de::Error::custom("graphs with a merge policy can't be deserialized")),
+            },
+            self_loops: graph.policy.self_loops,
+        };
+        let ret = Self::with_descriptors(graph.vertices,graph.edges,graph.free,graph.ordered).and_then(|g| g.with_checked_edge_policy(policy));
+
+        match ret {
+            Ok(g) => return Ok(g),
+            Err(e) => return Err(de::Error::custom(format!("invalid graph: {}",e))),
+        }
     }
 }
 
@@ -267,6 +1168,7 @@ impl<'a,V,E> MutableGraph<'a,V,E> for AdjacencyList<V,E> {
 mod test {
     use super::*;
     use std::collections::HashSet;
+    use search::{TreeIterator,TraversalOrder};
 
     #[test]
     fn test_node_attribute()
@@ -559,6 +1461,679 @@ mod test {
         assert_eq!(g.out_degree(n1), 1);
     }
 
+    #[test]
+    fn test_stale_descriptors()
+    {
+        let mut g = AdjacencyList::<isize,String>::new();
+
+        let n1 = g.add_vertex(42);
+        let n2 = g.add_vertex(13);
+        let n3 = g.add_vertex(1337);
+
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e22 = g.add_edge("b".to_string(),n2,n2).unwrap();
+        let e23 = g.add_edge("c".to_string(),n2,n3).unwrap();
+
+        assert_eq!(g.remove_vertex(n2), Some(13));
+        assert_eq!(g.num_edges(), 0);
+
+        let n4 = g.add_vertex(99);
+        let e14 = g.add_edge("d".to_string(),n1,n4).unwrap();
+
+        // the freed slots are reused, the stale descriptors must not alias the new elements
+        assert_eq!(n4.0, n2.0);
+        assert!(n4 != n2);
+        assert!(e14 != e12 && e14 != e22 && e14 != e23);
+
+        assert_eq!(g.vertex_label(n2), None);
+        assert_eq!(g.vertex_label(n4), Some(&99));
+        assert_eq!(g.edge_label(e12), None);
+        assert_eq!(g.edge_label(e14), Some(&"d".to_string()));
+        assert_eq!(g.out_degree(n2), 0);
+        assert_eq!(g.in_degree(n2), 0);
+        assert_eq!(g.add_edge("e".to_string(),n2,n1), None);
+        assert_eq!(g.remove_vertex(n2), None);
+        assert_eq!(g.remove_edge(e12), None);
+        assert!(g.vertex_label_mut(n2).is_none());
+
+        // descriptors of the remaining elements are stable
+        assert_eq!(g.vertex_label(n1), Some(&42));
+        assert_eq!(g.vertex_label(n3), Some(&1337));
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 1);
+        assert_eq!(g.vertices().collect::<Vec<_>>(), vec![n1,n4,n3]);
+    }
+
+    #[test]
+    fn test_index()
+    {
+        let mut g = AdjacencyList::<isize,String>::new();
+
+        let n1 = g.add_vertex(42);
+        let n2 = g.add_vertex(13);
+        let n3 = g.add_vertex(1337);
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e23 = g.add_edge("b".to_string(),n2,n3).unwrap();
+
+        g.remove_vertex(n1);
+
+        assert_eq!(g.vertex_index_bound(), 3);
+        assert_eq!(g.edge_index_bound(), 2);
+        assert!(g.vertices().all(|v| g.vertex_index(v) < g.vertex_index_bound()));
+        assert!(g.vertex_index(n2) != g.vertex_index(n3));
+        assert!(g.edge_index(e12) != g.edge_index(e23));
+
+        let n4 = g.add_vertex(99);
+
+        assert_eq!(g.vertex_index(n4), g.vertex_index(n1));
+        assert_eq!(g.vertex_index_bound(), 3);
+    }
+
+    #[test]
+    fn test_fallible()
+    {
+        let mut g = AdjacencyList::<isize,String>::new();
+
+        let n1 = g.add_vertex(42);
+        let n2 = g.add_vertex(13);
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e21 = g.add_edge("b".to_string(),n2,n1).unwrap();
+
+        assert_eq!(g.try_source(e12), Ok(n1));
+        assert_eq!(g.try_target(e12), Ok(n2));
+        assert_eq!(g.try_out_degree(n1), Ok(1));
+        assert_eq!(g.try_out_edges(n1).map(|i| i.collect::<Vec<_>>()), Ok(vec![e12]));
+        assert_eq!(g.try_in_edges(n1).map(|i| i.collect::<Vec<_>>()), Ok(vec![e21]));
+
+        assert_eq!(g.try_remove_vertex(n2), Ok(13));
+
+        assert_eq!(g.try_source(e12), Err(GraphError::UnknownEdge));
+        assert_eq!(g.try_target(e21), Err(GraphError::UnknownEdge));
+        assert_eq!(g.try_edge_label(e12), Err(GraphError::UnknownEdge));
+        assert_eq!(g.try_vertex_label(n2), Err(GraphError::UnknownVertex));
+        assert!(g.try_out_edges(n2).is_err());
+        assert!(g.try_in_edges(n2).is_err());
+        assert!(g.try_adjacent_vertices(n2).is_err());
+        assert_eq!(g.try_in_degree(n2), Err(GraphError::UnknownVertex));
+        assert_eq!(g.try_degree(n2), Err(GraphError::UnknownVertex));
+        assert_eq!(g.out_edges(n2).count(), 0);
+        assert_eq!(g.in_edges(n2).count(), 0);
+        assert_eq!(g.adjacent_vertices(n2).count(), 0);
+        assert_eq!(g.try_add_edge("c".to_string(),n1,n2), Err(GraphError::UnknownVertex));
+        assert_eq!(g.try_remove_edge(e12), Err(GraphError::UnknownEdge));
+        assert_eq!(g.try_remove_vertex(n2), Err(GraphError::UnknownVertex));
+    }
+
+    #[test]
+    fn test_atomic_removal()
+    {
+        let mut g = AdjacencyList::<isize,String>::new();
+
+        let n1 = g.add_vertex(42);
+        let n2 = g.add_vertex(13);
+        let n3 = g.add_vertex(1337);
+
+        let e11 = g.add_edge("a".to_string(),n1,n1).unwrap();
+        let e12 = g.add_edge("b".to_string(),n1,n2).unwrap();
+        let e31 = g.add_edge("c".to_string(),n3,n1).unwrap();
+
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        // corrupt the in list of n2, removing n1 must fail without changing anything
+        g.vertex_entry_mut(n2).unwrap().in_edges.clear();
+
+        assert_eq!(g.check_invariants(), Err(GraphError::Inconsistent));
+        assert_eq!(g.remove_vertex(n1), None);
+        assert_eq!(g.remove_edge(e12), None);
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.vertex_label(n1), Some(&42));
+        assert_eq!(g.out_degree(n1), 2);
+        assert_eq!(g.in_degree(n1), 2);
+        assert_eq!(g.edge_label(e11), Some(&"a".to_string()));
+        assert_eq!(g.edge_label(e31), Some(&"c".to_string()));
+
+        g.vertex_entry_mut(n2).unwrap().in_edges.push(e12);
+
+        assert_eq!(g.check_invariants(), Ok(()));
+        assert_eq!(g.remove_vertex(n1), Some(42));
+        assert_eq!(g.num_edges(), 0);
+        assert_eq!(g.out_degree(n3), 0);
+        assert_eq!(g.in_degree(n2), 0);
+        assert_eq!(g.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_undirected()
+    {
+        let mut g = AdjacencyList::<isize,String,UndirectedEdges>::undirected();
+
+        let n1 = g.add_vertex(42);
+        let n2 = g.add_vertex(13);
+        let n3 = g.add_vertex(1337);
+
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e23 = g.add_edge("b".to_string(),n2,n3).unwrap();
+        let e33 = g.add_edge("c".to_string(),n3,n3).unwrap();
+
+        assert!(!g.is_directed());
+        assert_eq!(g.check_invariants(), Ok(()));
+        assert_eq!(g.out_edges(n2).collect::<Vec<_>>(), vec![e12,e23]);
+        assert_eq!(g.in_edges(n2).collect::<Vec<_>>(), vec![e12,e23]);
+        assert_eq!(g.out_edges(n3).collect::<Vec<_>>(), vec![e23,e33]);
+        assert_eq!(g.degree(n2), 2);
+        assert_eq!(g.out_degree(n3), 2);
+        assert_eq!(g.degree(n3), 3);
+        assert_eq!(g.source(e12), n1);
+        assert_eq!(g.opposite(e12,n2), n1);
+        assert_eq!(g.opposite(e33,n3), n3);
+        assert_eq!(g.edge(n2,n1), Some(e12));
+        assert_eq!(g.adjacent_vertices(n2).collect::<HashSet<_>>(), [n1,n3].iter().cloned().collect::<HashSet<_>>());
+
+        let pre = TreeIterator::new(n3,TraversalOrder::Preorder,&g).collect::<Vec<_>>();
+        assert_eq!(pre, vec![n3,n2,n1]);
+
+        assert_eq!(g.remove_edge(e23), Some("b".to_string()));
+        assert_eq!(g.out_edges(n2).collect::<Vec<_>>(), vec![e12]);
+        assert_eq!(g.out_edges(n3).collect::<Vec<_>>(), vec![e33]);
+        assert_eq!(g.check_invariants(), Ok(()));
+        assert_eq!(g.remove_vertex(n1), Some(42));
+        assert_eq!(g.degree(n2), 0);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        use serde_json;
+        let g2: AdjacencyList<isize,String,UndirectedEdges> = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+        assert_eq!(g2.out_edges(n3).collect::<Vec<_>>(), vec![e33]);
+        assert_eq!(g2.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_edge_policy()
+    {
+        let mut g = AdjacencyList::<isize,usize>::new();
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+
+        let e1 = g.add_edge(1,n1,n2).unwrap();
+        let e2 = g.add_edge(2,n1,n2).unwrap();
+        g.add_edge(3,n2,n1).unwrap();
+        g.add_edge(4,n1,n1).unwrap();
+
+        assert_eq!(g.edges_between(n1,n2).collect::<Vec<_>>(), vec![e1,e2]);
+        assert_eq!(g.num_edges(), 4);
+
+        let policy = EdgePolicy{ parallel_edges: ParallelEdges::Reject, self_loops: false };
+        let mut g = AdjacencyList::<isize,usize>::new().with_edge_policy(policy);
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+
+        let e1 = g.add_edge(1,n1,n2).unwrap();
+
+        assert_eq!(g.try_add_edge(2,n1,n2), Err(GraphError::EdgeExists));
+        assert_eq!(g.insert_edge_at(2,n1,n2,0), Err(GraphError::EdgeExists));
+        assert_eq!(g.try_add_edge(3,n1,n1), Err(GraphError::SelfLoop));
+        assert!(g.add_edge(4,n2,n1).is_some());
+        assert_eq!(g.edges_between(n1,n2).collect::<Vec<_>>(), vec![e1]);
+        assert_eq!(g.num_edges(), 2);
+
+        let factor = 2;
+        let policy = EdgePolicy{ parallel_edges: ParallelEdges::merge(move |a: &mut usize,b| *a += factor * b), self_loops: true };
+        let mut g = AdjacencyList::<isize,usize,UndirectedEdges>::undirected().with_edge_policy(policy);
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+
+        let e1 = g.add_edge(1,n1,n2).unwrap();
+
+        assert_eq!(g.add_edge(2,n2,n1), Some(e1));
+        assert_eq!(g.add_edge(3,n1,n2), Some(e1));
+        assert_eq!(g.edge_label(e1), Some(&11));
+        assert_eq!(g.edges_between(n2,n1).collect::<Vec<_>>(), vec![e1]);
+        assert_eq!(g.num_edges(), 1);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        // the policy is saved, merge functions are not
+        use serde_json;
+        let json = serde_json::to_string(&g).unwrap();
+
+        assert!(json.contains("\"policy\":{\"parallel_edges\":\"Merge\",\"self_loops\":true}"));
+        assert_eq!(serde_json::from_str::<AdjacencyList<isize,usize,UndirectedEdges>>(&json).err().map(|e| e.to_string().starts_with("graphs with a merge policy")), Some(true));
+
+        let policy = EdgePolicy{ parallel_edges: ParallelEdges::Reject, self_loops: false };
+        let mut g = AdjacencyList::<isize,usize>::new();
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+
+        g.add_edge(1,n1,n2);
+        g.add_edge(2,n1,n1);
+        assert_eq!(g.clone().with_checked_edge_policy(policy.clone()).err(), Some(GraphError::SelfLoop));
+
+        g.add_edge(3,n1,n2);
+        g.add_edge(4,n2,n1);
+        assert_eq!(g.clone().with_checked_edge_policy(EdgePolicy{ self_loops: true, ..policy.clone() }).err(), Some(GraphError::EdgeExists));
+
+        let g = AdjacencyList::<isize,usize>::new().with_edge_policy(policy);
+        let g2: AdjacencyList<isize,usize> = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+
+        assert!(!g2.edge_policy().self_loops);
+        assert!(matches!(g2.edge_policy().parallel_edges,ParallelEdges::Reject));
+
+        let json = "{\"version\":1,\"directed\":true,\"ordered\":false,\"policy\":{\"parallel_edges\":\"Reject\",\"self_loops\":false},\"vertices\":[[[0,0],1]],\"edges\":[[[0,0],[0,0],[0,0],1]],\"free\":{\"vertices\":[],\"edges\":[],\"vertex_generation\":0,\"edge_generation\":0}}";
+        assert_eq!(serde_json::from_str::<AdjacencyList<isize,usize>>(json).err().map(|e| e.to_string().starts_with("invalid graph: self-loop")), Some(true));
+    }
+
+    #[test]
+    fn test_bulk_construction()
+    {
+        let vertices = vec![("a",1),("b",2),("c",3)];
+        let edges = vec![("a","b",12),("b","c",23),("c","a",31)];
+        let (g,ids) = AdjacencyList::<isize,usize>::from_edges(vertices,edges).unwrap();
+
+        assert_eq!(ids.len(), 3);
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.vertex_label(ids["a"]), Some(&1));
+        assert_eq!(g.edges_between(ids["b"],ids["c"]).map(|e| g.edge_label(e)).collect::<Vec<_>>(), vec![Some(&23)]);
+        assert_eq!(AdjacencyList::<isize,usize>::from_edges(vec![("a",1)],vec![("a","b",1)]).err(), Some(GraphError::UnknownVertex));
+        assert_eq!(AdjacencyList::<isize,usize>::from_edges(vec![("a",1),("a",2)],Vec::<(&str,&str,usize)>::new()).err(), Some(GraphError::DuplicateKey));
+
+        let (g,ids) = AdjacencyList::<isize,usize,UndirectedEdges>::undirected_from_edges(vec![("a",1),("b",2)],vec![("a","b",12)]).unwrap();
+        assert_eq!(g.degree(ids["b"]), 1);
+
+        let mut g = vec![(1,2,12),(2,3,23)].into_iter().collect::<AdjacencyList<usize,usize>>();
+
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 2);
+
+        let v3 = g.vertices().find(|&v| g.vertex_label(v) == Some(&3)).unwrap();
+        let v1 = g.vertices().find(|&v| g.vertex_label(v) == Some(&1)).unwrap();
+        let v4 = g.add_vertex(4);
+
+        g.reserve(10,10);
+        g.extend(vec![(v3,v1,31),(v3,v4,34)]);
+
+        let labels = |g: &AdjacencyList<usize,usize>| {
+            let mut ret = g.edges().map(|e| (*g.vertex_label(g.source(e)).unwrap(),*g.vertex_label(g.target(e)).unwrap(),*g.edge_label(e).unwrap())).collect::<Vec<_>>();
+            ret.sort();
+            ret
+        };
+
+        assert_eq!(g.num_vertices(), 4);
+        assert_eq!(labels(&g), vec![(1,2,12),(2,3,23),(3,1,31),(3,4,34)]);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        let mut g = AdjacencyList::<usize,usize>::new().with_edge_policy(EdgePolicy{ parallel_edges: ParallelEdges::Reject, self_loops: true });
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+
+        assert_eq!(g.try_extend(vec![(v1,v2,12),(v2,v1,21),(v1,v2,0),(v2,v2,22)]), Err(GraphError::EdgeExists));
+        assert_eq!(g.num_edges(), 2);
+
+        let v3 = g.add_vertex(3);
+
+        g.remove_vertex(v3);
+        g.extend(vec![(v1,v2,0),(v1,v3,13),(v2,v2,22)]);
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.edges_between(v2,v2).map(|e| *g.edge_label(e).unwrap()).collect::<Vec<_>>(), vec![22]);
+        assert_eq!(g.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_map()
+    {
+        let mut g = AdjacencyList::<isize,String>::new();
+
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+        let n3 = g.add_vertex(3);
+        let n4 = g.add_vertex(4);
+
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e13 = g.add_edge("bb".to_string(),n1,n3).unwrap();
+        let e14 = g.add_edge("ccc".to_string(),n1,n4).unwrap();
+        let e23 = g.add_edge("dddd".to_string(),n2,n3).unwrap();
+
+        let h = g.map(|_,&x| x * 100,|_,x| x.len());
+
+        assert_eq!(h.vertex_label(n3), Some(&300));
+        assert_eq!(h.edge_label(e14), Some(&3));
+        assert_eq!(h.out_edges(n1).collect::<Vec<_>>(), g.out_edges(n1).collect::<Vec<_>>());
+        assert_eq!(h.check_invariants(), Ok(()));
+
+        let mut h = g.filter_map(|v,&x| if v != n2 { Some(x as usize) } else { None },|e,x| if e != e14 { Some(x.clone()) } else { None });
+
+        assert_eq!(h.num_vertices(), 3);
+        assert_eq!(h.num_edges(), 1);
+        assert_eq!(h.vertex_label(n2), None);
+        assert_eq!(h.edge_label(e12), None);
+        assert_eq!(h.edge_label(e23), None);
+        assert_eq!(h.edge_label(e13), Some(&"bb".to_string()));
+        assert_eq!(h.out_edges(n1).collect::<Vec<_>>(), vec![e13]);
+        assert_eq!(h.check_invariants(), Ok(()));
+
+        let n5 = h.add_vertex(5);
+        let e = h.add_edge("e".to_string(),n1,n5).unwrap();
+
+        assert!(n5 != n2);
+        assert!(e != e12 && e != e14 && e != e23);
+        assert_eq!(h.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_contraction()
+    {
+        let mut g = AdjacencyList::<Vec<usize>,usize>::new();
+
+        let n1 = g.add_vertex(vec![1]);
+        let n2 = g.add_vertex(vec![2]);
+        let n3 = g.add_vertex(vec![3]);
+        let n4 = g.add_vertex(vec![4]);
+
+        let e12 = g.add_edge(12,n1,n2).unwrap();
+        let e23 = g.add_edge(23,n2,n3).unwrap();
+        let e32 = g.add_edge(32,n3,n2).unwrap();
+        let e24 = g.add_edge(24,n2,n4).unwrap();
+        let e34 = g.add_edge(34,n3,n4).unwrap();
+
+        // collapse the loop 2 <-> 3, keeping the resulting self-loop
+        assert_eq!(g.contract_edge(e23,|a,b| a.extend(b),false), Ok(n2));
+        assert_eq!(g.vertex_label(n2), Some(&vec![2,3]));
+        assert_eq!(g.vertex_label(n3), None);
+        assert_eq!(g.edge_label(e23), None);
+        assert_eq!(g.source(e32), n2);
+        assert_eq!(g.target(e32), n2);
+        assert_eq!(g.source(e34), n2);
+        assert_eq!(g.out_edges(n2).collect::<Vec<_>>(), vec![e24,e32,e34]);
+        assert_eq!(g.in_edges(n2).collect::<Vec<_>>(), vec![e12,e32]);
+        assert_eq!(g.check_invariants(), Ok(()));
+        assert_eq!(g.contract_edge(e32,|_,_| {},false), Err(GraphError::SelfLoop));
+
+        assert_eq!(g.merge_vertices(n4,n2,|a,b| a.extend(b),true), Ok(()));
+        assert_eq!(g.vertex_label(n4), Some(&vec![4,2,3]));
+        assert_eq!(g.num_edges(), 1);
+        assert_eq!(g.edge_label(e12), Some(&12));
+        assert_eq!(g.target(e12), n4);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        let mut g = AdjacencyList::<usize,(),UndirectedEdges>::undirected();
+
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+        let n3 = g.add_vertex(3);
+
+        let e12 = g.add_edge((),n1,n2).unwrap();
+        let e21 = g.add_edge((),n2,n1).unwrap();
+        let e23 = g.add_edge((),n2,n3).unwrap();
+
+        assert_eq!(g.contract_edge(e12,|a,b| *a += b,false), Ok(n1));
+        assert_eq!(g.vertex_label(n1), Some(&3));
+        assert_eq!(g.out_edges(n1).collect::<Vec<_>>(), vec![e21,e23]);
+        assert_eq!(g.opposite(e23,n1), n3);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        // the edge policy applies to the moved edges
+        let policy = EdgePolicy{ parallel_edges: ParallelEdges::Reject, self_loops: true };
+        let mut g = AdjacencyList::<usize,usize>::new().with_edge_policy(policy);
+
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+        let n3 = g.add_vertex(3);
+
+        let e13 = g.add_edge(13,n1,n3).unwrap();
+        g.add_edge(23,n2,n3).unwrap();
+
+        assert_eq!(g.merge_vertices(n1,n2,|_,_| {},false), Err(GraphError::EdgeExists));
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.out_edges(n1).collect::<Vec<_>>(), vec![e13]);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        let policy = EdgePolicy{ parallel_edges: ParallelEdges::merge(|a: &mut usize,b| *a += b), self_loops: false };
+        let mut g = AdjacencyList::<usize,usize>::new().with_edge_policy(policy);
+
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+        let n3 = g.add_vertex(3);
+        let n4 = g.add_vertex(4);
+
+        let e12 = g.add_edge(12,n1,n2).unwrap();
+        let e13 = g.add_edge(13,n1,n3).unwrap();
+        let e23 = g.add_edge(23,n2,n3).unwrap();
+        let e21 = g.add_edge(21,n2,n1).unwrap();
+        let e34 = g.add_edge(34,n3,n4).unwrap();
+
+        assert_eq!(g.contract_edge(e12,|a,b| *a += b,false), Ok(n1));
+        assert_eq!(g.edge_label(e12), None);
+        assert_eq!(g.edge_label(e21), None);
+        assert_eq!(g.edge_label(e23), None);
+        assert_eq!(g.edge_label(e13), Some(&36));
+        assert_eq!(g.out_edges(n1).collect::<Vec<_>>(), vec![e13]);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        assert_eq!(g.merge_vertices(n3,n1,|a,b| *a += b,false), Ok(()));
+        assert_eq!(g.num_edges(), 1);
+        assert_eq!(g.edge_label(e34), Some(&34));
+        assert_eq!(g.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_compact()
+    {
+        let mut g = AdjacencyList::<isize,String>::new();
+
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+        let n3 = g.add_vertex(3);
+        let n4 = g.add_vertex(4);
+
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e23 = g.add_edge("b".to_string(),n2,n3).unwrap();
+        let e34 = g.add_edge("c".to_string(),n3,n4).unwrap();
+        let e41 = g.add_edge("d".to_string(),n4,n1).unwrap();
+
+        g.remove_vertex(n2);
+        g.remove_edge(e34);
+
+        assert_eq!(g.vertex_index_bound(), 4);
+        assert_eq!(g.edge_index_bound(), 4);
+
+        let (vm,em) = g.compact();
+
+        assert_eq!(vm.len(), 3);
+        assert_eq!(em.len(), 1);
+        assert!(!vm.contains_key(&n2));
+        assert!(!em.contains_key(&e12) && !em.contains_key(&e23) && !em.contains_key(&e34));
+        assert_eq!(g.vertex_index_bound(), 3);
+        assert_eq!(g.edge_index_bound(), 1);
+        assert_eq!(g.vertices().collect::<Vec<_>>(), vec![vm[&n1],vm[&n3],vm[&n4]]);
+        assert_eq!(g.vertices().map(|v| g.vertex_index(v)).collect::<Vec<_>>(), vec![0,1,2]);
+        assert_eq!(g.vertex_label(vm[&n4]), Some(&4));
+        assert_eq!(g.edge_label(em[&e41]), Some(&"d".to_string()));
+        assert_eq!(g.source(em[&e41]), vm[&n4]);
+        assert_eq!(g.target(em[&e41]), vm[&n1]);
+        assert_eq!(g.out_edges(vm[&n4]).collect::<Vec<_>>(), vec![em[&e41]]);
+        assert_eq!(g.check_invariants(), Ok(()));
+
+        // descriptors from before the compaction are stale, even where their slot is in use
+        assert_eq!(n1.0, vm[&n1].0);
+        assert_eq!(g.vertex_label(n1), None);
+        assert_eq!(g.vertex_label(n3), None);
+        assert_eq!(g.edge_label(e12), None);
+        assert_eq!(g.edge_label(e41), None);
+
+        let n5 = g.add_vertex(5);
+
+        assert_eq!(n5.0, n4.0);
+        assert_eq!(g.vertex_label(n4), None);
+        assert_eq!(g.vertex_label(n5), Some(&5));
+    }
+
+    #[test]
+    fn test_ordered_edges()
+    {
+        let mut g = AdjacencyList::<isize,String>::with_ordered_edges();
+
+        let n1 = g.add_vertex(42);
+        let n2 = g.add_vertex(13);
+        let n3 = g.add_vertex(1337);
+        let n4 = g.add_vertex(99);
+
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e13 = g.add_edge("b".to_string(),n1,n3).unwrap();
+        let e14 = g.add_edge("c".to_string(),n1,n4).unwrap();
+        let e32 = g.add_edge("d".to_string(),n3,n2).unwrap();
+        let e42 = g.add_edge("e".to_string(),n4,n2).unwrap();
+
+        assert!(g.has_ordered_edges());
+        assert!(g.remove_edge(e12).is_some());
+        assert_eq!(g.out_edges(n1).collect::<Vec<_>>(), vec![e13,e14]);
+
+        let e12 = g.insert_edge_at("f".to_string(),n1,n2,0).unwrap();
+
+        assert_eq!(g.out_edges(n1).collect::<Vec<_>>(), vec![e12,e13,e14]);
+        assert_eq!(g.in_edges(n2).collect::<Vec<_>>(), vec![e32,e42,e12]);
+        assert_eq!(g.insert_edge_at("g".to_string(),n1,n2,4), Err(GraphError::InvalidPosition));
+        assert_eq!(g.num_edges(), 5);
+
+        assert!(g.remove_edge(e32).is_some());
+        assert_eq!(g.in_edges(n2).collect::<Vec<_>>(), vec![e42,e12]);
+
+        assert_eq!(g.reorder_out_edges(n1,&[e14,e12]), Err(GraphError::InvalidOrder));
+        assert_eq!(g.reorder_out_edges(n1,&[e14,e12,e12]), Err(GraphError::InvalidOrder));
+        assert_eq!(g.reorder_out_edges(n1,&[e14,e12,e13]), Ok(()));
+        assert_eq!(g.out_edges(n1).collect::<Vec<_>>(), vec![e14,e12,e13]);
+        assert_eq!(g.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn test_deterministic_order()
+    {
+        use serde_json;
+
+        let mut g = AdjacencyList::<usize,usize>::new();
+        let vs = (0..20).map(|i| g.add_vertex(i)).collect::<Vec<_>>();
+
+        for i in 0..19 {
+            g.add_edge(i,vs[i],vs[i + 1]);
+            g.add_edge(i + 100,vs[19 - i],vs[i]);
+        }
+
+        assert_eq!(g.vertices().collect::<Vec<_>>(), vs);
+        assert_eq!(g.vertices().map(|v| *g.vertex_label(v).unwrap()).collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+
+        let es = g.edges().collect::<Vec<_>>();
+        let mut sorted = es.clone();
+
+        sorted.sort();
+        assert_eq!(es, sorted);
+        assert_eq!(g.edges().map(|e| *g.edge_label(e).unwrap()).take(4).collect::<Vec<_>>(), vec![0,100,1,101]);
+
+        g.remove_vertex(vs[3]);
+        g.remove_vertex(vs[7]);
+        let n = g.add_vertex(99);
+
+        let vs2 = g.vertices().collect::<Vec<_>>();
+        let mut sorted = vs2.clone();
+
+        sorted.sort();
+        assert_eq!(vs2, sorted);
+        assert!(vs2.contains(&n));
+
+        let g2 = g.clone();
+        let g3: AdjacencyList<usize,usize> = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+
+        assert_eq!(g2.vertices().collect::<Vec<_>>(), vs2);
+        assert_eq!(g3.vertices().collect::<Vec<_>>(), vs2);
+        assert_eq!(g2.edges().collect::<Vec<_>>(), g.edges().collect::<Vec<_>>());
+        assert_eq!(g3.edges().collect::<Vec<_>>(), g.edges().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_serialization()
+    {
+        use serde_json;
+
+        let mut g = AdjacencyList::<usize,String>::with_ordered_edges();
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+        let n3 = g.add_vertex(3);
+
+        let e12 = g.add_edge("a".to_string(),n1,n2).unwrap();
+        let e13 = g.add_edge("b".to_string(),n1,n3).unwrap();
+        let e11 = g.add_edge("c".to_string(),n1,n1).unwrap();
+        let e32 = g.add_edge("d".to_string(),n3,n2).unwrap();
+
+        g.remove_vertex(n2);
+        g.reorder_out_edges(n1,&[e11,e13]).unwrap();
+
+        let n4 = g.add_vertex(4);
+        let e41 = g.add_edge("e".to_string(),n4,n1).unwrap();
+        let json = serde_json::to_string(&g).unwrap();
+
+        assert!(json.starts_with("{\"version\":1,\"directed\":true,\"ordered\":true,"));
+        assert!(!json.contains("out_edges"));
+
+        let g2: AdjacencyList<usize,String> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(g2.check_invariants(), Ok(()));
+        assert!(g2.has_ordered_edges());
+        assert_eq!(g2.vertices().collect::<Vec<_>>(), vec![n1,n4,n3]);
+        assert_eq!(g2.edges().collect::<Vec<_>>(), g.edges().collect::<Vec<_>>());
+        assert_eq!(g2.vertex_label(n2), None);
+        assert_eq!(g2.edge_label(e12), None);
+        assert_eq!(g2.edge_label(e32), None);
+        assert_eq!(g2.edge_label(e41), Some(&"e".to_string()));
+        assert_eq!(g2.out_edges(n1).collect::<Vec<_>>(), vec![e11,e13]);
+        assert_eq!(g2.in_edges(n1).collect::<Vec<_>>().len(), 2);
+
+        // descriptors of removed elements stay invalid when slots are reused
+        let mut g2 = g2;
+        let n5 = g2.add_vertex(5);
+
+        assert!(n5 != n2 && n5 != n1 && n5 != n3 && n5 != n4);
+
+        // also for edges, and after compaction
+        let e43 = g2.add_edge("f".to_string(),n4,n3).unwrap();
+
+        assert!(e43 != e12 && e43 != e32);
+        assert_eq!(g2.edge_label(e12), None);
+        assert_eq!(g2.edge_label(e32), None);
+
+        let mut g = g;
+        g.compact();
+
+        let mut g2: AdjacencyList<usize,String> = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+        let n6 = g2.add_vertex(6);
+
+        assert_eq!(g2.vertex_label(n1), None);
+        assert_eq!(g2.vertex_label(n6), Some(&6));
+        assert!(n6 != n4);
+
+        let u: AdjacencyList<usize,String,UndirectedEdges> = AdjacencyList::undirected_from_edges(vec![(1,1),(2,2)],vec![(1,2,"x".to_string())]).unwrap().0;
+        let json = serde_json::to_string(&u).unwrap();
+        let u2: AdjacencyList<usize,String,UndirectedEdges> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(u2.num_edges(), 1);
+        assert_eq!(u2.check_invariants(), Ok(()));
+        assert!(serde_json::from_str::<AdjacencyList<usize,String>>(&json).is_err());
+
+        // corrupted input
+        let load = |json: &str| serde_json::from_str::<AdjacencyList<usize,()>>(json).err().map(|e| e.to_string());
+        let graph = |vertices: &str, edges: &str, free: &str| format!("{{\"version\":1,\"directed\":true,\"ordered\":false,\"policy\":{{\"parallel_edges\":\"Allow\",\"self_loops\":true}},\"vertices\":{},\"edges\":{},\"free\":{{{}\"vertex_generation\":0,\"edge_generation\":0}}}}",vertices,edges,free);
+        let vertices = "[[[0,0],1],[[1,0],2]]";
+        let no_free = "\"vertices\":[],\"edges\":[],";
+
+        assert_eq!(load(&graph(vertices,"[[[0,0],[0,0],[1,0],null]]",no_free)), None);
+        assert_eq!(load(&graph("[[[0,0],1],[[2,0],2]]","[]","\"vertices\":[[1,3]],\"edges\":[],")), None);
+        assert!(load(&graph(vertices,"[]",no_free).replace("\"version\":1","\"version\":2")).unwrap().starts_with("unsupported format version 2"));
+        assert!(load(&graph(vertices,"[[[0,0],[0,0],[1,1],null]]",no_free)).unwrap().starts_with("invalid graph: unknown vertex"));
+        assert!(load(&graph(vertices,"[[[0,0],[0,0],[1,0],null],[[0,1],[1,0],[0,0],null]]",no_free)).unwrap().starts_with("invalid graph: inconsistent graph"));
+        assert!(load(&graph("[[[0,0],1],[[0,3],2]]","[]",no_free)).is_some());
+        assert!(load(&graph("[[[0,0],1],[[2,0],2]]","[]",no_free)).unwrap().starts_with("invalid graph: inconsistent graph"));
+        assert!(load(&graph("[[[1099511627776,0],1]]","[]",no_free)).unwrap().starts_with("invalid graph: inconsistent graph"));
+        assert!(load(&graph(vertices,"[]","\"vertices\":[[1,1]],\"edges\":[],")).unwrap().starts_with("invalid graph: inconsistent graph"));
+        assert!(load(&graph(vertices,"[]",no_free).replace("\"directed\":true","\"directed\":false")).unwrap().starts_with("expected a directed graph"));
+        assert!(load("{\"vertices\":[],\"edges\":[]}").is_some());
+    }
+
     #[test]
     fn test_edge() {
         let mut g = AdjacencyList::<isize,String>::new();
@@ -1,5 +1,10 @@
+use error::GraphError;
 use traits::*;
 use std::ops::Range;
+use std::marker::PhantomData;
+use std::convert::TryFrom;
+use std::iter::{Enumerate,FilterMap};
+use std::slice::Iter;
 
 #[derive(Eq,Hash,PartialEq,Clone,Debug,Copy)]
 pub struct AdjacencyMatrixEdgeDescriptor {
@@ -11,15 +16,19 @@ pub struct AdjacencyMatrixEdgeDescriptor {
 pub struct AdjacencyMatrix<'a,V:'a,E:'a> {
     edges: &'a [&'a [Option<E>]],
     vertex_labels: &'a [V],
+    num_edges: usize,
 }
 
 impl<'a,V,E> AdjacencyMatrix<'a,V,E> {
     pub fn new(edges: &'a [&'a [Option<E>]], vertices: &'a [V]) -> Self {
         assert_eq!(edges.len(), vertices.len());
 
+        let num_edges = edges.iter().map(|row| row.iter().filter(|x| x.is_some()).count()).sum();
+
         return AdjacencyMatrix {
             edges: edges,
             vertex_labels: vertices,
+            num_edges: num_edges,
         }
     }
 }
@@ -29,11 +38,11 @@ impl<'a,V,E> Graph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
     type Edge = AdjacencyMatrixEdgeDescriptor;
 
     fn edge_label(&self,e: Self::Edge) -> Option<&E> {
-        return self.edges[e.from][e.to].as_ref();
+        return self.edges.get(e.from).and_then(|row| row.get(e.to)).and_then(|x| x.as_ref());
     }
 
     fn vertex_label(&self,v: Self::Vertex) -> Option<&V> {
-        return Some(&self.vertex_labels[v]);
+        return self.vertex_labels.get(v);
     }
 
     fn source(&self,e: Self::Edge) -> Self::Vertex {
@@ -47,23 +56,22 @@ impl<'a,V,E> Graph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
 
 impl<'a,V,E> AdjacencyMatrixGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
     fn edge(&'a self,from: Self::Vertex,to: Self::Vertex) -> Option<Self::Edge> {
-        if self.edges[from][to].is_some() {
-            return Some(AdjacencyMatrixEdgeDescriptor{ from: from, to: to });
-        } else {
-            return None;
-        }
+        let desc = AdjacencyMatrixEdgeDescriptor{ from: from, to: to };
+
+        return self.edge_label(desc).map(|_| desc);
     }
 }
 
 #[derive(Debug)]
-pub struct AdjacencyMatrixNeight<'a,V:'a,E:'a> {
+pub struct AdjacencyMatrixNeight<'a,E:'a,R:'a + AsRef<[Option<E>]>> {
     fix: usize,
     var: Range<usize>,
-    mat: &'a AdjacencyMatrix<'a,V,E>,
+    rows: &'a [R],
     dir: bool, // true <=> Out
+    _marker: PhantomData<E>,
 }
 
-impl<'a,V,E> Iterator for AdjacencyMatrixNeight<'a,V,E> {
+impl<'a,E,R: AsRef<[Option<E>]>> Iterator for AdjacencyMatrixNeight<'a,E,R> {
     type Item = AdjacencyMatrixEdgeDescriptor;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -71,13 +79,13 @@ impl<'a,V,E> Iterator for AdjacencyMatrixNeight<'a,V,E> {
 
         while n.is_some() {
             if self.dir {
-                if self.mat.edges[self.fix][n.unwrap()].is_some() {
+                if self.rows[self.fix].as_ref()[n.unwrap()].is_some() {
                     return Some(AdjacencyMatrixEdgeDescriptor{ from: self.fix, to: n.unwrap() })
                 } else {
                     n = self.var.next();
                 }
             } else {
-                if self.mat.edges[n.unwrap()][self.fix].is_some() {
+                if self.rows[n.unwrap()].as_ref()[self.fix].is_some() {
                     return Some(AdjacencyMatrixEdgeDescriptor{ to: self.fix, from: n.unwrap() })
                 } else {
                     n = self.var.next();
@@ -90,33 +98,35 @@ impl<'a,V,E> Iterator for AdjacencyMatrixNeight<'a,V,E> {
 }
 
 impl<'a,V,E> IncidenceGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
-    type Incidence = AdjacencyMatrixNeight<'a,V,E>;
+    type Incidence = AdjacencyMatrixNeight<'a,E,&'a [Option<E>]>;
 
     fn out_degree(&'a self, from: Self::Vertex) -> usize {
-        return (0..self.vertex_labels.len()).fold(0,|acc,x| return acc + if self.edges[from][x].is_some() { 1 } else { 0 });
+        return (0..self.vertex_labels.len()).filter(|&x| self.edge(from,x).is_some()).count();
     }
 
     fn out_edges(&'a self, from: Self::Vertex) -> Self::Incidence {
         return AdjacencyMatrixNeight{
             fix: from,
-            var: (0..self.vertex_labels.len()),
-            mat: self,
-            dir: true
+            var: (0..if from < self.vertex_labels.len() { self.vertex_labels.len() } else { 0 }),
+            rows: self.edges,
+            dir: true,
+            _marker: PhantomData,
         };
     }
 }
 
 impl<'a,V,E> BidirectionalGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
     fn in_degree(&'a self, to: Self::Vertex) -> usize {
-        return (0..self.vertex_labels.len()).fold(0,|acc,x| return acc + if self.edges[x][to].is_some() { 1 } else { 0 });
+        return (0..self.vertex_labels.len()).filter(|&x| self.edge(x,to).is_some()).count();
     }
 
     fn in_edges(&'a self, to: Self::Vertex) -> Self::Incidence {
         return AdjacencyMatrixNeight{
             fix: to,
-            var: (0..self.vertex_labels.len()),
-            mat: self,
-            dir: false
+            var: (0..if to < self.vertex_labels.len() { self.vertex_labels.len() } else { 0 }),
+            rows: self.edges,
+            dir: false,
+            _marker: PhantomData,
         };
     }
 
@@ -125,6 +135,76 @@ impl<'a,V,E> BidirectionalGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
     }
 }
 
+impl<'a,V,E> VertexIndexGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return v;
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.vertex_labels.len();
+    }
+}
+
+impl<'a,V,E> EdgeIndexGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return e.from * self.vertex_labels.len() + e.to;
+    }
+
+    fn edge_index_bound(&self) -> usize {
+        return self.vertex_labels.len() * self.vertex_labels.len();
+    }
+}
+
+/// Iterates all occupied cells of an adjacency matrix in row-major order.
+#[derive(Debug)]
+pub struct AdjacencyMatrixEdges<'a,E:'a,R:'a + AsRef<[Option<E>]>> {
+    from: usize,
+    to: usize,
+    rows: &'a [R],
+    _marker: PhantomData<E>,
+}
+
+impl<'a,E,R: AsRef<[Option<E>]>> Iterator for AdjacencyMatrixEdges<'a,E,R> {
+    type Item = AdjacencyMatrixEdgeDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.from < self.rows.len() {
+            let row = self.rows[self.from].as_ref();
+
+            while self.to < row.len() {
+                let to = self.to;
+
+                self.to += 1;
+                if row[to].is_some() {
+                    return Some(AdjacencyMatrixEdgeDescriptor{ from: self.from, to: to });
+                }
+            }
+
+            self.from += 1;
+            self.to = 0;
+        }
+
+        return None;
+    }
+}
+
+impl<'a,V,E> EdgeListGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
+    type Edges = AdjacencyMatrixEdges<'a,E,&'a [Option<E>]>;
+
+    fn num_edges(&self) -> usize {
+        return self.num_edges;
+    }
+
+    fn edges(&'a self) -> Self::Edges {
+        return AdjacencyMatrixEdges{
+            from: 0,
+            to: 0,
+            rows: self.edges,
+            _marker: PhantomData,
+        };
+    }
+}
+
 #[derive(Debug)]
 pub struct AdjacencyMatrixAdjacency {
     adj: Box<Vec<usize>>
@@ -165,6 +245,316 @@ impl<'a,V,E> VertexListGraph<'a,V,E> for AdjacencyMatrix<'a,V,E> {
     }
 }
 
+/// Adjacency matrix that owns its vertex and edge labels.
+///
+/// Unlike `AdjacencyMatrix` the graph can be modified after construction. Removed vertices leave
+/// a free slot behind that is reused by the next `add_vertex` call, so vertex descriptors of the
+/// remaining vertices stay valid. Like any adjacency matrix it holds at most one edge per ordered
+/// pair of vertices.
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(try_from = "SerializedAdjacencyMatrix<V,E>")]
+pub struct OwnedAdjacencyMatrix<V,E> {
+    edges: Vec<Vec<Option<E>>>,
+    vertex_labels: Vec<Option<V>>,
+    num_vertices: usize,
+    num_edges: usize,
+}
+
+/// `OwnedAdjacencyMatrix` as read, before its rows and counts are checked.
+#[derive(Deserialize)]
+struct SerializedAdjacencyMatrix<V,E> {
+    edges: Vec<Vec<Option<E>>>,
+    vertex_labels: Vec<Option<V>>,
+    num_vertices: usize,
+    num_edges: usize,
+}
+
+impl<V,E> TryFrom<SerializedAdjacencyMatrix<V,E>> for OwnedAdjacencyMatrix<V,E> {
+    type Error = String;
+
+    fn try_from(m: SerializedAdjacencyMatrix<V,E>) -> Result<Self,String> {
+        let n = m.vertex_labels.len();
+
+        if m.edges.len() != n || m.edges.iter().any(|row| row.len() != n) {
+            return Err(format!("expected a {} x {} matrix",n,n));
+        }
+
+        for (from,row) in m.edges.iter().enumerate() {
+            for (to,x) in row.iter().enumerate() {
+                if x.is_some() && (m.vertex_labels[from].is_none() || m.vertex_labels[to].is_none()) {
+                    return Err(format!("edge {} {} connects a removed vertex",from,to));
+                }
+            }
+        }
+
+        if m.vertex_labels.iter().filter(|x| x.is_some()).count() != m.num_vertices {
+            return Err("wrong number of vertices".to_string());
+        }
+
+        if m.edges.iter().map(|row| row.iter().filter(|x| x.is_some()).count()).sum::<usize>() != m.num_edges {
+            return Err("wrong number of edges".to_string());
+        }
+
+        return Ok(OwnedAdjacencyMatrix{
+            edges: m.edges,
+            vertex_labels: m.vertex_labels,
+            num_vertices: m.num_vertices,
+            num_edges: m.num_edges,
+        });
+    }
+}
+
+impl<V,E> Default for OwnedAdjacencyMatrix<V,E> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<V,E> OwnedAdjacencyMatrix<V,E> {
+    pub fn new() -> Self {
+        return OwnedAdjacencyMatrix {
+            edges: Vec::new(),
+            vertex_labels: Vec::new(),
+            num_vertices: 0,
+            num_edges: 0,
+        };
+    }
+
+    fn is_vertex(&self, v: usize) -> bool {
+        return self.vertex_labels.get(v).is_some_and(|x| x.is_some());
+    }
+}
+
+impl<V: Clone,E: Clone> OwnedAdjacencyMatrix<V,E> {
+    /// Copies the contents of a borrowed adjacency matrix. Vertex descriptors are the same in
+    /// both graphs.
+    pub fn from_matrix<'a>(mat: &AdjacencyMatrix<'a,V,E>) -> Self {
+        let edges = mat.edges.iter().map(|row| row.to_vec()).collect::<Vec<_>>();
+        let num_edges = edges.iter().map(|row| row.iter().filter(|x| x.is_some()).count()).sum();
+
+        return OwnedAdjacencyMatrix {
+            edges: edges,
+            vertex_labels: mat.vertex_labels.iter().cloned().map(Some).collect(),
+            num_vertices: mat.vertex_labels.len(),
+            num_edges: num_edges,
+        };
+    }
+}
+
+impl<'a,V,E> Graph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    type Vertex = usize;
+    type Edge = AdjacencyMatrixEdgeDescriptor;
+
+    fn edge_label(&self,e: Self::Edge) -> Option<&E> {
+        return self.edges.get(e.from).and_then(|row| row.get(e.to)).and_then(|x| x.as_ref());
+    }
+
+    fn vertex_label(&self,v: Self::Vertex) -> Option<&V> {
+        return self.vertex_labels.get(v).and_then(|x| x.as_ref());
+    }
+
+    fn source(&self,e: Self::Edge) -> Self::Vertex {
+        return e.from;
+    }
+
+    fn target(&self,e: Self::Edge) -> Self::Vertex {
+        return e.to;
+    }
+}
+
+impl<'a,V,E> AdjacencyMatrixGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    fn edge(&'a self,from: Self::Vertex,to: Self::Vertex) -> Option<Self::Edge> {
+        let desc = AdjacencyMatrixEdgeDescriptor{ from: from, to: to };
+
+        return self.edge_label(desc).map(|_| desc);
+    }
+}
+
+impl<'a,V,E: 'a> IncidenceGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    type Incidence = AdjacencyMatrixNeight<'a,E,Vec<Option<E>>>;
+
+    fn out_degree(&'a self, from: Self::Vertex) -> usize {
+        return self.edges.get(from).map_or(0,|row| row.iter().filter(|x| x.is_some()).count());
+    }
+
+    fn out_edges(&'a self, from: Self::Vertex) -> Self::Incidence {
+        return AdjacencyMatrixNeight{
+            fix: from,
+            var: (0..if from < self.vertex_labels.len() { self.vertex_labels.len() } else { 0 }),
+            rows: &self.edges,
+            dir: true,
+            _marker: PhantomData,
+        };
+    }
+}
+
+impl<'a,V,E: 'a> BidirectionalGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    fn in_degree(&'a self, to: Self::Vertex) -> usize {
+        return self.edges.iter().filter(|row| row.get(to).is_some_and(|x| x.is_some())).count();
+    }
+
+    fn in_edges(&'a self, to: Self::Vertex) -> Self::Incidence {
+        return AdjacencyMatrixNeight{
+            fix: to,
+            var: (0..if to < self.vertex_labels.len() { self.vertex_labels.len() } else { 0 }),
+            rows: &self.edges,
+            dir: false,
+            _marker: PhantomData,
+        };
+    }
+
+    fn degree(&'a self, v: Self::Vertex) -> usize {
+        return self.in_degree(v) + self.out_degree(v);
+    }
+}
+
+impl<'a,V,E: 'a> AdjacencyGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    type Adjacency = AdjacencyMatrixAdjacency;
+
+    fn adjacent_vertices(&'a self, v: Self::Vertex) -> Self::Adjacency {
+        let i = self.out_edges(v).map(|x| return self.target(x));
+        let o = self.in_edges(v).map(|x| return self.source(x));
+        let mut raw = i.chain(o).collect::<Vec<usize>>();
+
+        raw.sort();
+        raw.dedup();
+
+        return AdjacencyMatrixAdjacency { adj: Box::new(raw) };
+    }
+}
+
+impl<'a,V: 'a,E: 'a> VertexListGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    type Vertices = FilterMap<Enumerate<Iter<'a,Option<V>>>,fn((usize,&Option<V>)) -> Option<usize>>;
+
+    fn vertices(&'a self) -> Self::Vertices {
+        fn live<V>((idx,lb): (usize,&Option<V>)) -> Option<usize> {
+            return lb.as_ref().map(|_| idx);
+        }
+
+        return self.vertex_labels.iter().enumerate().filter_map(live);
+    }
+
+    fn num_vertices(&self) -> usize {
+        return self.num_vertices;
+    }
+}
+
+impl<'a,V,E: 'a> EdgeListGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    type Edges = AdjacencyMatrixEdges<'a,E,Vec<Option<E>>>;
+
+    fn num_edges(&self) -> usize {
+        return self.num_edges;
+    }
+
+    fn edges(&'a self) -> Self::Edges {
+        return AdjacencyMatrixEdges{
+            from: 0,
+            to: 0,
+            rows: &self.edges,
+            _marker: PhantomData,
+        };
+    }
+}
+
+impl<'a,V,E> VertexIndexGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return v;
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.vertex_labels.len();
+    }
+}
+
+impl<'a,V,E> EdgeIndexGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return e.from * self.vertex_labels.len() + e.to;
+    }
+
+    fn edge_index_bound(&self) -> usize {
+        return self.vertex_labels.len() * self.vertex_labels.len();
+    }
+}
+
+impl<'a,V,E> MutableGraph<'a,V,E> for OwnedAdjacencyMatrix<V,E> {
+    fn add_vertex(&mut self, lb: V) -> Self::Vertex {
+        self.num_vertices += 1;
+
+        if let Some(v) = self.vertex_labels.iter().position(|x| x.is_none()) {
+            self.vertex_labels[v] = Some(lb);
+            return v;
+        }
+
+        let v = self.vertex_labels.len();
+
+        self.vertex_labels.push(Some(lb));
+        for row in self.edges.iter_mut() {
+            row.push(None);
+        }
+        self.edges.push((0..v + 1).map(|_| None).collect());
+
+        return v;
+    }
+
+    fn add_edge(&mut self, lb: E, from: Self::Vertex, to: Self::Vertex) -> Option<Self::Edge> {
+        return self.try_add_edge(lb,from,to).ok();
+    }
+
+    fn try_add_edge(&mut self, lb: E, from: Self::Vertex, to: Self::Vertex) -> Result<Self::Edge,GraphError> {
+        if !self.is_vertex(from) || !self.is_vertex(to) {
+            return Err(GraphError::UnknownVertex);
+        }
+        if self.edges[from][to].is_some() {
+            return Err(GraphError::EdgeExists);
+        }
+
+        self.edges[from][to] = Some(lb);
+        self.num_edges += 1;
+
+        return Ok(AdjacencyMatrixEdgeDescriptor{ from: from, to: to });
+    }
+
+    fn remove_vertex(&mut self, v: Self::Vertex) -> Option<V> {
+        if !self.is_vertex(v) {
+            return None;
+        }
+
+        let mut removed = 0;
+
+        for x in 0..self.vertex_labels.len() {
+            if self.edges[v][x].take().is_some() {
+                removed += 1;
+            }
+            if self.edges[x][v].take().is_some() {
+                removed += 1;
+            }
+        }
+
+        self.num_edges -= removed;
+        self.num_vertices -= 1;
+
+        return self.vertex_labels[v].take();
+    }
+
+    fn remove_edge(&mut self, e: Self::Edge) -> Option<E> {
+        let ret = self.edges.get_mut(e.from).and_then(|row| row.get_mut(e.to)).and_then(|x| x.take());
+
+        if ret.is_some() {
+            self.num_edges -= 1;
+        }
+
+        return ret;
+    }
+
+    fn vertex_label_mut(&mut self, v: Self::Vertex) -> Option<&mut V> {
+        return self.vertex_labels.get_mut(v).and_then(|x| x.as_mut());
+    }
+
+    fn edge_label_mut(&mut self, e: Self::Edge) -> Option<&mut E> {
+        return self.edges.get_mut(e.from).and_then(|row| row.get_mut(e.to)).and_then(|x| x.as_mut());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +588,14 @@ mod tests {
         assert_eq!(g.edge(3,1), None);
         assert_eq!(g.edge(3,2), None);
         assert_eq!(g.edge(3,3), None);
+        assert_eq!(g.edge(3,4), None);
+        assert_eq!(g.edge(4,0), None);
+        assert_eq!(g.try_vertex_label(4), Err(GraphError::UnknownVertex));
+        assert!(g.try_out_edges(4).is_err());
+        assert!(g.try_in_edges(4).is_err());
+        assert_eq!(g.out_degree(4), 0);
+        assert_eq!(g.in_degree(4), 0);
+        assert_eq!(g.out_edges(4).count(), 0);
     }
 
     #[test]
@@ -429,11 +827,18 @@ mod tests {
 
         assert!(e12.is_some() && e21.is_some() && e23.is_some() && e14.is_some());
 
+        type EdgeSet<'a> = HashSet<<AdjacencyMatrix<'a,isize,String> as Graph<'a,isize,String>>::Edge>;
         type VertexSet<'a> = HashSet<<AdjacencyMatrix<'a,isize,String> as Graph<'a,isize,String>>::Vertex>;
 
         let vs = g.vertices().collect::<VertexSet>();
         assert!(vs.contains(&n1) && vs.contains(&n2) && vs.contains(&n3) && vs.contains(&n4));
         assert_eq!(vs.len(), 4);
+
+        let es = g.edges().collect::<EdgeSet>();
+        assert!(es.contains(&e12.unwrap()) && es.contains(&e23.unwrap()) &&
+                es.contains(&e21.unwrap()) && es.contains(&e14.unwrap()));
+        assert_eq!(es.len(), 4);
+        assert_eq!(g.num_edges(), 4);
     }
 
     #[test]
@@ -457,5 +862,125 @@ mod tests {
         assert!(e12.is_some() && e23.is_some());
 
         assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 2);
+    }
+
+    #[test]
+    fn owned_usage()
+    {
+        let mut g = OwnedAdjacencyMatrix::<i16,String>::new();
+
+        let n1 = g.add_vertex(42);
+        let n2 = g.add_vertex(13);
+        let n3 = g.add_vertex(1337);
+
+        let e12 = g.add_edge("a".to_string(),n1,n2);
+        let e23 = g.add_edge("b".to_string(),n2,n3);
+        let e31 = g.add_edge("c".to_string(),n3,n1);
+
+        assert!(e12.is_some() && e23.is_some() && e31.is_some());
+        assert_eq!(g.try_add_edge("d".to_string(),n1,n2), Err(GraphError::EdgeExists));
+        assert_eq!(g.try_add_edge("d".to_string(),n1,n1+n2+n3), Err(GraphError::UnknownVertex));
+        assert_eq!(g.out_degree(n1+n2+n3), 0);
+        assert_eq!(g.in_degree(n1+n2+n3), 0);
+        assert_eq!(g.in_edges(n1+n2+n3).count(), 0);
+
+        assert_eq!(g.vertex_label(n1), Some(&42));
+        assert_eq!(g.edge_label(e23.unwrap()), Some(&"b".to_string()));
+        assert_eq!(g.edge(n1,n2), e12);
+        assert_eq!(g.edge(n2,n1), None);
+
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.out_degree(n1), 1);
+        assert_eq!(g.in_degree(n1), 1);
+        assert_eq!(g.degree(n2), 2);
+
+        *g.vertex_label_mut(n1).unwrap() = 69;
+        *g.edge_label_mut(e12.unwrap()).unwrap() = "x".to_string();
+
+        assert_eq!(g.vertex_label(n1), Some(&69));
+        assert_eq!(g.edge_label(e12.unwrap()), Some(&"x".to_string()));
+
+        assert_eq!(g.remove_edge(e31.unwrap()), Some("c".to_string()));
+        assert_eq!(g.remove_edge(e31.unwrap()), None);
+        assert_eq!(g.in_degree(n1), 0);
+
+        assert_eq!(g.remove_vertex(n2), Some(13));
+        assert_eq!(g.remove_vertex(n2), None);
+        assert_eq!(g.num_vertices(), 2);
+        assert_eq!(g.num_edges(), 0);
+        assert_eq!(g.edges().next(), None);
+        assert_eq!(g.vertex_label(n2), None);
+        assert_eq!(g.out_degree(n1), 0);
+        assert_eq!(g.in_degree(n3), 0);
+        assert_eq!(g.vertices().collect::<Vec<_>>(), vec![n1,n3]);
+
+        let n4 = g.add_vertex(99);
+
+        assert_eq!(n4, n2);
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.out_degree(n4), 0);
+        assert_eq!(g.in_degree(n4), 0);
+    }
+
+    #[test]
+    fn owned_from_matrix()
+    {
+        let v = [42,13,1337,99];
+        let e = [
+            &[None,Some("a".to_string()),None,Some("d".to_string())][..],
+            &[Some("c".to_string()),None,Some("b".to_string()),None][..],
+            &[None,None,None,None][..],
+            &[None,None,None,None][..]
+        ];
+
+        let g1 = AdjacencyMatrix::<i16,String>::new(&e[..],&v);
+        let mut g2 = OwnedAdjacencyMatrix::from_matrix(&g1);
+
+        assert_eq!(g2.num_vertices(), 4);
+        assert_eq!(g2.num_edges(), 4);
+        assert_eq!(g1.edges().collect::<Vec<_>>(), g2.edges().collect::<Vec<_>>());
+
+        for vx in g1.vertices() {
+            assert_eq!(g1.vertex_label(vx), g2.vertex_label(vx));
+            assert_eq!(g1.out_edges(vx).collect::<Vec<_>>(), g2.out_edges(vx).collect::<Vec<_>>());
+            assert_eq!(g1.in_edges(vx).collect::<Vec<_>>(), g2.in_edges(vx).collect::<Vec<_>>());
+            assert_eq!(g1.adjacent_vertices(vx).collect::<Vec<_>>(), g2.adjacent_vertices(vx).collect::<Vec<_>>());
+        }
+
+        let n5 = g2.add_vertex(7);
+
+        assert!(g2.add_edge("e".to_string(),n5,0).is_some());
+        assert_eq!(g2.in_degree(0), 2);
+        assert_eq!(g2.out_degree(n5), 1);
+    }
+
+    #[test]
+    fn owned_serialize()
+    {
+        use serde_json;
+
+        let mut g = OwnedAdjacencyMatrix::<i32,String>::new();
+        let n1 = g.add_vertex(1);
+        let n2 = g.add_vertex(2);
+        let n3 = g.add_vertex(3);
+
+        g.add_edge("a".to_string(),n1,n3);
+        g.remove_vertex(n2);
+
+        let h: OwnedAdjacencyMatrix<i32,String> = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+
+        assert_eq!(h.num_vertices(), 2);
+        assert_eq!(h.vertex_label(n3), Some(&3));
+        assert_eq!(h.edges().collect::<Vec<_>>(), g.edges().collect::<Vec<_>>());
+
+        let load = |json: &str| serde_json::from_str::<OwnedAdjacencyMatrix<i32,i32>>(json).err().map(|e| e.to_string());
+
+        assert_eq!(load("{\"edges\":[[null,1],[null]],\"vertex_labels\":[1,2],\"num_vertices\":2,\"num_edges\":1}"), Some("expected a 2 x 2 matrix".to_string()));
+        assert_eq!(load("{\"edges\":[[null]],\"vertex_labels\":[1,2],\"num_vertices\":2,\"num_edges\":0}"), Some("expected a 2 x 2 matrix".to_string()));
+        assert_eq!(load("{\"edges\":[[null,1],[null,null]],\"vertex_labels\":[1,null],\"num_vertices\":1,\"num_edges\":1}"), Some("edge 0 1 connects a removed vertex".to_string()));
+        assert_eq!(load("{\"edges\":[[null,1],[null,null]],\"vertex_labels\":[1,2],\"num_vertices\":2,\"num_edges\":2}"), Some("wrong number of edges".to_string()));
+        assert_eq!(load("{\"edges\":[[null,1],[null,null]],\"vertex_labels\":[1,2],\"num_vertices\":2,\"num_edges\":1}"), None);
     }
 }
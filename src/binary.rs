@@ -0,0 +1,748 @@
+//! Binary graph files that are used in place through a memory map.
+//!
+//! `write_binary` stores a graph in CSR form, `MappedGraph` maps such a file read-only and
+//! implements the graph traits directly on top of the mapped bytes. Opening a file checks its
+//! header in constant time, `open_verified` also checks the contents. Neither copies or decodes
+//! the graph.
+//!
+//! All numbers are little-endian. A file is a 64 byte header followed by four sections, each
+//! padded to a multiple of 8 bytes:
+//!
+//! ```text
+//!  0  magic "GRAPHBIN"          32  vertex label size (u32)
+//!  8  format version (u32)      36  edge label size (u32)
+//! 12  flags (u32)               40  reserved, 0 (u64)
+//! 16  number of vertices (u64)  48  reserved, 0 (u64)
+//! 24  number of edges (u64)     56  CRC-32 of the sections (u32)
+//!                               60  CRC-32 of bytes 0..60 (u32)
+//!
+//! out edge offsets    (vertices + 1) x u64, edges of vertex v are offsets[v]..offsets[v + 1]
+//! targets             edges x u64
+//! vertex labels       vertices x vertex label size
+//! edge labels         edges x edge label size
+//! ```
+//!
+//! Bit 0 of the flags is set for undirected graphs, all other bits are 0. `MappedGraph` only
+//! opens directed graphs, the file lacks the reverse edges undirected ones need. Labels are
+//! stored as their in-memory representation, so they must be `Plain`. Files can only be written
+//! and read on little-endian machines.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self,Write};
+use std::iter::Map;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+use std::slice;
+
+use byteorder::{ByteOrder,LittleEndian,WriteBytesExt};
+use crc32fast::Hasher;
+use memmap2::Mmap;
+
+use copy::DescriptorMaps;
+use csr::{CsrVertexDescriptor,CsrEdgeDescriptor};
+use traits::*;
+
+/// Version of the format written by `write_binary`.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 8] = b"GRAPHBIN";
+const HEADER_SIZE: usize = 64;
+const FLAG_UNDIRECTED: u32 = 1;
+
+/// Label types that are stored as their bytes. Mapped files only hold labels aligned to at most
+/// 8 bytes.
+///
+/// # Safety
+///
+/// Implementors must have no padding bytes and every bit pattern of their size must be a valid
+/// value.
+pub unsafe trait Plain: Copy + 'static {}
+
+unsafe impl Plain for () {}
+unsafe impl Plain for u8 {}
+unsafe impl Plain for u16 {}
+unsafe impl Plain for u32 {}
+unsafe impl Plain for u64 {}
+unsafe impl Plain for i8 {}
+unsafe impl Plain for i16 {}
+unsafe impl Plain for i32 {}
+unsafe impl Plain for i64 {}
+unsafe impl Plain for f32 {}
+unsafe impl Plain for f64 {}
+unsafe impl<T: Plain,const N: usize> Plain for [T; N] {}
+
+fn as_bytes<T: Plain>(x: &T) -> &[u8] {
+    // Plain types have no padding, so all bytes are initialized
+    return unsafe { slice::from_raw_parts(x as *const T as *const u8,mem::size_of::<T>()) };
+}
+
+/// Reasons writing or opening a binary graph file can fail.
+#[derive(Debug)]
+pub enum BinaryError {
+    /// The underlying file or writer failed.
+    Io(io::Error),
+    /// The file is no graph file, was written by an unsupported version, doesn't match the label
+    /// types or is corrupted.
+    Invalid(String),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BinaryError::Io(ref e) => write!(f,"{}",e),
+            BinaryError::Invalid(ref msg) => write!(f,"invalid graph file: {}",msg),
+        }
+    }
+}
+
+impl Error for BinaryError {}
+
+impl From<io::Error> for BinaryError {
+    fn from(e: io::Error) -> Self {
+        return BinaryError::Io(e);
+    }
+}
+
+fn invalid<T>(msg: String) -> Result<T,BinaryError> {
+    return Err(BinaryError::Invalid(msg));
+}
+
+fn check_endianness() -> Result<(),BinaryError> {
+    if cfg!(target_endian = "big") {
+        return invalid("binary graph files are little-endian only".to_string());
+    } else {
+        return Ok(());
+    }
+}
+
+/// Sections start at multiples of 8, so labels with a larger alignment can't be mapped.
+fn check_alignment<V,E>() -> Result<(),BinaryError> {
+    if mem::align_of::<V>() > 8 || mem::align_of::<E>() > 8 {
+        return invalid("labels must not need an alignment above 8 bytes".to_string());
+    } else {
+        return Ok(());
+    }
+}
+
+/// Bytes needed to pad `len` to a multiple of 8.
+fn padding(len: usize) -> usize {
+    return (8 - len % 8) % 8;
+}
+
+/// Offsets of the sections of a file, computed from the header with overflow checks.
+#[derive(Debug)]
+struct Layout {
+    offsets: usize,
+    targets: usize,
+    vertex_labels: usize,
+    edge_labels: usize,
+    end: usize,
+}
+
+impl Layout {
+    fn new(vertices: u64, edges: u64, vertex_size: u64, edge_size: u64) -> Option<Layout> {
+        let section = |count: u64, size: u64| -> Option<u64> {
+            let len = count.checked_mul(size)?;
+            return len.checked_add((8 - len % 8) % 8);
+        };
+        let offsets = HEADER_SIZE as u64;
+        let targets = offsets.checked_add(section(vertices.checked_add(1)?,8)?)?;
+        let vertex_labels = targets.checked_add(section(edges,8)?)?;
+        let edge_labels = vertex_labels.checked_add(section(vertices,vertex_size)?)?;
+        let end = edge_labels.checked_add(section(edges,edge_size)?)?;
+
+        if end > usize::MAX as u64 {
+            return None;
+        }
+
+        return Some(Layout{
+            offsets: offsets as usize,
+            targets: targets as usize,
+            vertex_labels: vertex_labels as usize,
+            edge_labels: edge_labels as usize,
+            end: end as usize,
+        });
+    }
+}
+
+/// Writes `graph` in the binary format. Vertices are numbered in `vertices()` order, the out
+/// edges of each vertex are in `edges()` order. `direction` is recorded in the file, pass
+/// `UndirectedEdges` for undirected graphs. Their edges are stored once, from source to target,
+/// and `MappedGraph` doesn't open such files.
+///
+/// Returns maps from the descriptors of `graph` to the ones of a `MappedGraph` of the file.
+pub fn write_binary<'a,V,E,D,G,W>(graph: &'a G, _direction: D, mut out: W) -> Result<DescriptorMaps<G::Vertex,CsrVertexDescriptor,G::Edge,CsrEdgeDescriptor>,BinaryError>
+    where V: Plain, E: Plain, D: Direction, G: 'a + VertexListGraph<'a,V,E> + EdgeListGraph<'a,V,E>, W: Write {
+    check_endianness()?;
+    check_alignment::<V,E>()?;
+
+    let vertices = graph.vertices().collect::<Vec<_>>();
+    let index = vertices.iter().enumerate().map(|(i,&v)| (v,i)).collect::<HashMap<_,_>>();
+    let n = vertices.len();
+    let mut offsets = vec![0u64; n + 1];
+
+    for e in graph.edges() {
+        offsets[index[&graph.source(e)] + 1] += 1;
+    }
+
+    for i in 0..n {
+        offsets[i + 1] += offsets[i];
+    }
+
+    let m = offsets[n] as usize;
+    let mut next = offsets.clone();
+    let mut edges = vec![None; m];
+
+    for e in graph.edges() {
+        let from = index[&graph.source(e)];
+
+        edges[next[from] as usize] = Some(e);
+        next[from] += 1;
+    }
+
+    let edges = edges.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+    let targets = edges.iter().map(|&e| index[&graph.target(e)] as u64).collect::<Vec<_>>();
+    let vertex_labels = vertices.iter().map(|&v| graph.try_vertex_label(v)).collect::<Result<Vec<_>,_>>()
+        .map_err(|e| BinaryError::Invalid(e.to_string()))?;
+    let edge_labels = edges.iter().map(|&e| graph.try_edge_label(e)).collect::<Result<Vec<_>,_>>()
+        .map_err(|e| BinaryError::Invalid(e.to_string()))?;
+
+    // the sections are produced twice, for the checksum and for the output
+    let sections = |f: &mut dyn FnMut(&[u8]) -> io::Result<()>| -> io::Result<()> {
+        let mut buf = [0u8; 8];
+        let zeros = [0u8; 8];
+
+        for &x in offsets.iter().chain(targets.iter()) {
+            LittleEndian::write_u64(&mut buf,x);
+            f(&buf)?;
+        }
+
+        for lb in vertex_labels.iter() {
+            f(as_bytes(*lb))?;
+        }
+        f(&zeros[..padding(n * mem::size_of::<V>())])?;
+
+        for lb in edge_labels.iter() {
+            f(as_bytes(*lb))?;
+        }
+        f(&zeros[..padding(m * mem::size_of::<E>())])?;
+
+        return Ok(());
+    };
+
+    let mut data_crc = Hasher::new();
+
+    sections(&mut |b| { data_crc.update(b); Ok(()) })?;
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+
+    header.extend_from_slice(MAGIC);
+    header.write_u32::<LittleEndian>(FORMAT_VERSION)?;
+    header.write_u32::<LittleEndian>(if D::DIRECTED { 0 } else { FLAG_UNDIRECTED })?;
+    header.write_u64::<LittleEndian>(n as u64)?;
+    header.write_u64::<LittleEndian>(m as u64)?;
+    header.write_u32::<LittleEndian>(mem::size_of::<V>() as u32)?;
+    header.write_u32::<LittleEndian>(mem::size_of::<E>() as u32)?;
+    header.write_u64::<LittleEndian>(0)?;
+    header.write_u64::<LittleEndian>(0)?;
+    header.write_u32::<LittleEndian>(data_crc.finalize())?;
+
+    let header_crc = crc32fast::hash(&header);
+
+    header.write_u32::<LittleEndian>(header_crc)?;
+    out.write_all(&header)?;
+    sections(&mut |b| out.write_all(b))?;
+    out.flush()?;
+
+    let vertex_map = vertices.into_iter().enumerate().map(|(i,v)| (v,CsrVertexDescriptor(i))).collect();
+    let edge_map = edges.into_iter().enumerate().map(|(i,e)| (e,CsrEdgeDescriptor(i))).collect();
+
+    return Ok((vertex_map,edge_map));
+}
+
+/// Read-only graph backed by a memory-mapped file written by `write_binary`.
+///
+/// Only out edges are stored, `AdjacencyGraph` returns the targets of the out edges and `source`
+/// takes logarithmic time. Vertex and edge labels are references into the map.
+///
+/// The file must not be modified or truncated while it is mapped.
+#[derive(Debug)]
+pub struct MappedGraph<V,E> {
+    map: Mmap,
+    num_vertices: usize,
+    num_edges: usize,
+    layout: Layout,
+    labels: PhantomData<(V,E)>,
+}
+
+impl<V: Plain,E: Plain> MappedGraph<V,E> {
+    /// Maps the file at `path` read-only and checks its header, see `from_mmap`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the graph exists. Changed contents break
+    /// the checks done when opening, accessing pages cut off by truncation kills the process with
+    /// `SIGBUS`.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self,BinaryError> {
+        let file = File::open(path)?;
+        let map = Mmap::map(&file)?;
+
+        return Self::from_mmap(map);
+    }
+
+    /// Like `open`, but also verifies the contents, see `verify`.
+    ///
+    /// # Safety
+    ///
+    /// See `open`.
+    pub unsafe fn open_verified<P: AsRef<Path>>(path: P) -> Result<Self,BinaryError> {
+        let ret = Self::open(path)?;
+
+        ret.verify()?;
+        return Ok(ret);
+    }
+
+    /// Graph in the mapped file. Checks in constant time that the map is 8 byte aligned, the
+    /// header, that the graph is directed, that the label sizes match `V` and `E` and that the
+    /// file size matches the header.
+    /// The contents of the sections are not checked: a corrupted file can make the graph methods
+    /// panic or return wrong results, but never read outside of the map. Use `verify` for
+    /// untrusted files.
+    pub fn from_mmap(map: Mmap) -> Result<Self,BinaryError> {
+        check_endianness()?;
+        check_alignment::<V,E>()?;
+
+        if map.as_ptr().align_offset(8) != 0 {
+            return invalid("map is not 8 byte aligned".to_string());
+        }
+
+        if map.len() < HEADER_SIZE || &map[0..8] != MAGIC {
+            return invalid("not a graph file".to_string());
+        }
+
+        let header = &map[..HEADER_SIZE];
+
+        if crc32fast::hash(&header[..60]) != LittleEndian::read_u32(&header[60..64]) {
+            return invalid("header checksum mismatch".to_string());
+        }
+
+        let version = LittleEndian::read_u32(&header[8..12]);
+
+        if version != FORMAT_VERSION {
+            return invalid(format!("unsupported format version {}",version));
+        }
+
+        let flags = LittleEndian::read_u32(&header[12..16]);
+
+        if flags & !FLAG_UNDIRECTED != 0 {
+            return invalid(format!("unknown flags {:#x}",flags & !FLAG_UNDIRECTED));
+        }
+
+        if flags & FLAG_UNDIRECTED != 0 {
+            return invalid("undirected graph files are not supported".to_string());
+        }
+
+        if LittleEndian::read_u64(&header[40..48]) != 0 || LittleEndian::read_u64(&header[48..56]) != 0 {
+            return invalid("reserved header fields are not 0".to_string());
+        }
+
+        let n = LittleEndian::read_u64(&header[16..24]);
+        let m = LittleEndian::read_u64(&header[24..32]);
+        let vertex_size = LittleEndian::read_u32(&header[32..36]) as usize;
+        let edge_size = LittleEndian::read_u32(&header[36..40]) as usize;
+
+        if vertex_size != mem::size_of::<V>() || edge_size != mem::size_of::<E>() {
+            return invalid(format!("labels have {} and {} bytes, expected {} and {}",vertex_size,edge_size,mem::size_of::<V>(),mem::size_of::<E>()));
+        }
+
+        let layout = match Layout::new(n,m,vertex_size as u64,edge_size as u64) {
+            Some(layout) => layout,
+            None => return invalid("file size doesn't match header".to_string()),
+        };
+
+        if layout.end != map.len() {
+            return invalid("file size doesn't match header".to_string());
+        }
+
+        return Ok(MappedGraph{
+            map: map,
+            num_vertices: n as usize,
+            num_edges: m as usize,
+            layout: layout,
+            labels: PhantomData,
+        });
+    }
+
+    /// Checks the data checksum and that all offsets and targets are in bounds, which takes one
+    /// pass over the file.
+    pub fn verify(&self) -> Result<(),BinaryError> {
+        if crc32fast::hash(&self.map[HEADER_SIZE..]) != LittleEndian::read_u32(&self.map[56..60]) {
+            return invalid("data checksum mismatch".to_string());
+        }
+
+        if self.offset(0) != 0 || self.offset(self.num_vertices) != self.num_edges {
+            return invalid("edge offsets don't cover the edges".to_string());
+        }
+
+        for v in 0..self.num_vertices {
+            if self.offset(v) > self.offset(v + 1) {
+                return invalid(format!("edge offsets of vertex {} decrease",v));
+            }
+        }
+
+        for e in 0..self.num_edges {
+            if self.target_index(e) >= self.num_vertices {
+                return invalid(format!("target of edge {} out of bounds",e));
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+impl<V,E> MappedGraph<V,E> {
+    fn read_u64(&self, pos: usize) -> u64 {
+        return LittleEndian::read_u64(&self.map[pos..pos + 8]);
+    }
+
+    fn offset(&self, v: usize) -> usize {
+        return self.read_u64(self.layout.offsets + 8 * v) as usize;
+    }
+
+    fn target_index(&self, e: usize) -> usize {
+        return self.read_u64(self.layout.targets + 8 * e) as usize;
+    }
+
+    fn label<T>(&self, section: usize, idx: usize) -> &T {
+        // `from_mmap` checked that the map is 8 byte aligned and that T needs no more, sections
+        // start at multiples of 8 and the size of a type is a multiple of its alignment, so the
+        // label is aligned. It also checked that the section holds size_of::<T>() bytes per
+        // element.
+        return unsafe { &*(self.map.as_ptr().add(section + idx * mem::size_of::<T>()) as *const T) };
+    }
+}
+
+impl<'a,V,E> Graph<'a,V,E> for MappedGraph<V,E> {
+    type Vertex = CsrVertexDescriptor;
+    type Edge = CsrEdgeDescriptor;
+
+    fn edge_label(&self, e: Self::Edge) -> Option<&E> {
+        if e.0 < self.num_edges {
+            return Some(self.label(self.layout.edge_labels,e.0));
+        } else {
+            return None;
+        }
+    }
+
+    fn vertex_label(&self, v: Self::Vertex) -> Option<&V> {
+        if v.0 < self.num_vertices {
+            return Some(self.label(self.layout.vertex_labels,v.0));
+        } else {
+            return None;
+        }
+    }
+
+    fn source(&self, e: Self::Edge) -> Self::Vertex {
+        // first vertex whose edges end after e
+        let mut lo = 0;
+        let mut hi = self.num_vertices;
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+
+            if self.offset(mid + 1) <= e.0 {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        return CsrVertexDescriptor(lo);
+    }
+
+    fn target(&self, e: Self::Edge) -> Self::Vertex {
+        return CsrVertexDescriptor(self.target_index(e.0));
+    }
+}
+
+impl<'a,V,E> IncidenceGraph<'a,V,E> for MappedGraph<V,E> {
+    type Incidence = Map<Range<usize>,fn(usize) -> CsrEdgeDescriptor>;
+
+    fn out_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.offset(v.0 + 1) - self.offset(v.0);
+    }
+
+    fn out_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return (self.offset(v.0)..self.offset(v.0 + 1)).map(CsrEdgeDescriptor);
+    }
+}
+
+impl<'a,V,E> AdjacencyGraph<'a,V,E> for MappedGraph<V,E> {
+    type Adjacency = ::std::vec::IntoIter<CsrVertexDescriptor>;
+
+    fn adjacent_vertices(&'a self, v: Self::Vertex) -> Self::Adjacency {
+        let mut raw = self.out_edges(v).map(|e| self.target(e)).collect::<Vec<_>>();
+
+        raw.sort();
+        raw.dedup();
+
+        return raw.into_iter();
+    }
+}
+
+impl<'a,V,E> VertexListGraph<'a,V,E> for MappedGraph<V,E> {
+    type Vertices = Map<Range<usize>,fn(usize) -> CsrVertexDescriptor>;
+
+    fn vertices(&'a self) -> Self::Vertices {
+        return (0..self.num_vertices).map(CsrVertexDescriptor);
+    }
+
+    fn num_vertices(&self) -> usize {
+        return self.num_vertices;
+    }
+}
+
+impl<'a,V,E> EdgeListGraph<'a,V,E> for MappedGraph<V,E> {
+    type Edges = Map<Range<usize>,fn(usize) -> CsrEdgeDescriptor>;
+
+    fn num_edges(&self) -> usize {
+        return self.num_edges;
+    }
+
+    fn edges(&'a self) -> Self::Edges {
+        return (0..self.num_edges).map(CsrEdgeDescriptor);
+    }
+}
+
+impl<'a,V,E> VertexIndexGraph<'a,V,E> for MappedGraph<V,E> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return v.0;
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.num_vertices;
+    }
+}
+
+impl<'a,V,E> EdgeIndexGraph<'a,V,E> for MappedGraph<V,E> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return e.0;
+    }
+
+    fn edge_index_bound(&self) -> usize {
+        return self.num_edges;
+    }
+}
+
+impl<'a,V,E> AdjacencyMatrixGraph<'a,V,E> for MappedGraph<V,E> {
+    fn edge(&'a self, from: Self::Vertex, to: Self::Vertex) -> Option<Self::Edge> {
+        return self.out_edges(from).find(|&e| self.target_index(e.0) == to.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use adjacency_list::AdjacencyList;
+    use search::{TreeIterator,TraversalOrder};
+    use memmap2::MmapOptions;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("graph_algos-{}-{}",name,::std::process::id()));
+
+        fs::write(&path,bytes).unwrap();
+        return path;
+    }
+
+    fn sample() -> AdjacencyList<u32,[f32; 2]> {
+        let mut g = AdjacencyList::<u32,[f32; 2]>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+
+        g.add_edge([1.0,2.0],v3,v4);
+        g.add_edge([0.5,0.0],v1,v2);
+        g.add_edge([1.5,0.0],v1,v3);
+        g.add_edge([2.5,1.0],v2,v4);
+        g.add_edge([4.0,4.0],v4,v4);
+
+        return g;
+    }
+
+    #[test]
+    fn round_trip() {
+        let g = sample();
+        let mut buf = vec![];
+        let (vm,em) = write_binary(&g,DirectedEdges,&mut buf).unwrap();
+        let path = temp_file("round_trip",&buf);
+        let h = unsafe { MappedGraph::<u32,[f32; 2]>::open_verified(&path) }.unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(buf.len() % 8, 0);
+        assert_eq!(h.num_vertices(), 4);
+        assert_eq!(h.num_edges(), 5);
+
+        for v in g.vertices() {
+            assert_eq!(h.vertex_label(vm[&v]), g.vertex_label(v));
+            assert_eq!(h.out_degree(vm[&v]), g.out_degree(v));
+        }
+
+        for e in g.edges() {
+            assert_eq!(h.edge_label(em[&e]), g.edge_label(e));
+            assert_eq!(h.source(em[&e]), vm[&g.source(e)]);
+            assert_eq!(h.target(em[&e]), vm[&g.target(e)]);
+        }
+
+        let v1 = CsrVertexDescriptor(0);
+        let v4 = CsrVertexDescriptor(3);
+
+        assert_eq!(h.out_edges(v1).map(|e| h.edge_label(e).unwrap()[0]).collect::<Vec<_>>(), vec![0.5,1.5]);
+        assert_eq!(h.adjacent_vertices(v4).collect::<Vec<_>>(), vec![v4]);
+        assert_eq!(h.edge(v4,v4), Some(CsrEdgeDescriptor(4)));
+        assert_eq!(h.vertex_label(CsrVertexDescriptor(4)), None);
+
+        // algorithms work on the mapped graph
+        let pre = TreeIterator::new(v1,TraversalOrder::Preorder,&h).collect::<Vec<_>>();
+
+        assert_eq!(pre.len(), 4);
+        assert_eq!(pre[0], v1);
+    }
+
+    #[test]
+    fn empty_and_unlabeled() {
+        let g = AdjacencyList::<(),()>::new();
+        let mut buf = vec![];
+
+        write_binary(&g,DirectedEdges,&mut buf).unwrap();
+
+        let path = temp_file("empty",&buf);
+        let h = unsafe { MappedGraph::<(),()>::open(&path) }.unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(h.num_vertices(), 0);
+        assert_eq!(h.vertices().count(), 0);
+        assert!(h.verify().is_ok());
+    }
+
+    #[test]
+    fn rejects_corruption() {
+        let mut buf = vec![];
+
+        write_binary(&sample(),DirectedEdges,&mut buf).unwrap();
+
+        let open = |name: &str, bytes: &[u8]| {
+            let path = temp_file(name,bytes);
+            let ret = unsafe { MappedGraph::<u32,[f32; 2]>::open_verified(&path) }.err().map(|e| e.to_string());
+
+            fs::remove_file(&path).unwrap();
+            ret
+        };
+        // recomputes the checksums after a change
+        let fix = |bytes: &mut Vec<u8>| {
+            let crc = crc32fast::hash(&bytes[HEADER_SIZE..]);
+
+            LittleEndian::write_u32(&mut bytes[56..60],crc);
+
+            let crc = crc32fast::hash(&bytes[..60]);
+
+            LittleEndian::write_u32(&mut bytes[60..64],crc);
+        };
+
+        let mut data = buf.clone();
+        data[HEADER_SIZE + 8] ^= 1;
+        assert_eq!(open("data",&data), Some("invalid graph file: data checksum mismatch".to_string()));
+
+        // only checked on request
+        let path = temp_file("unverified",&data);
+        let h = unsafe { MappedGraph::<u32,[f32; 2]>::open(&path) }.unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert!(h.verify().is_err());
+
+        let mut header = buf.clone();
+        header[16] = 5;
+        assert_eq!(open("header",&header), Some("invalid graph file: header checksum mismatch".to_string()));
+
+        assert_eq!(open("short",&buf[..buf.len() - 8]), Some("invalid graph file: file size doesn't match header".to_string()));
+        assert_eq!(open("magic",&[0u8; 64]), Some("invalid graph file: not a graph file".to_string()));
+
+        let path = temp_file("types",&buf);
+        let err = unsafe { MappedGraph::<u64,[f32; 2]>::open(&path) }.err().map(|e| e.to_string());
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(err, Some("invalid graph file: labels have 4 and 8 bytes, expected 8 and 8".to_string()));
+
+        // consistent checksums, but a target out of bounds
+        let mut bad = buf.clone();
+        let targets = HEADER_SIZE + 8 * 5;
+
+        LittleEndian::write_u64(&mut bad[targets..targets + 8],9);
+        fix(&mut bad);
+        assert_eq!(open("target",&bad), Some("invalid graph file: target of edge 0 out of bounds".to_string()));
+
+        // unknown flags and reserved fields
+        let mut bad = buf.clone();
+
+        bad[13] = 1;
+        fix(&mut bad);
+        assert_eq!(open("flags",&bad), Some("invalid graph file: unknown flags 0x100".to_string()));
+
+        let mut bad = buf.clone();
+
+        bad[47] = 1;
+        fix(&mut bad);
+        assert_eq!(open("reserved",&bad), Some("invalid graph file: reserved header fields are not 0".to_string()));
+    }
+
+    #[test]
+    fn direction_and_alignment() {
+        let mut g = AdjacencyList::<u32,u64,UndirectedEdges>::undirected();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+
+        g.add_edge(12,v1,v2);
+
+        let mut buf = vec![];
+
+        write_binary(&g,UndirectedEdges,&mut buf).unwrap();
+        assert_eq!(LittleEndian::read_u32(&buf[12..16]), FLAG_UNDIRECTED);
+
+        let path = temp_file("undirected",&buf);
+        let err = unsafe { MappedGraph::<u32,u64>::open(&path) }.err().map(|e| e.to_string());
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(err, Some("invalid graph file: undirected graph files are not supported".to_string()));
+
+        // the file starts 4 bytes into the map
+        let mut shifted = vec![0u8; 4];
+
+        shifted.extend_from_slice(&buf);
+
+        let path = temp_file("aligned",&shifted);
+        let file = fs::File::open(&path).unwrap();
+        let map = unsafe { MmapOptions::new().offset(4).map(&file) }.unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(MappedGraph::<u32,u64>::from_mmap(map).err().map(|e| e.to_string()), Some("invalid graph file: map is not 8 byte aligned".to_string()));
+
+        #[derive(Clone,Copy)]
+        #[repr(align(16))]
+        struct Wide;
+
+        unsafe impl Plain for Wide {}
+
+        let mut g = AdjacencyList::<Wide,()>::new();
+
+        g.add_vertex(Wide);
+        assert!(write_binary(&g,DirectedEdges,Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Copying graphs between representations.
+
+use std::collections::HashMap;
+
+use error::GraphError;
+use traits::{
+    VertexListGraph,
+    EdgeListGraph,
+    MutableGraph,
+};
+
+/// Vertex and edge descriptors of the source graph mapped to the ones of the copy.
+pub type DescriptorMaps<A,B,C,D> = (HashMap<A,B>,HashMap<C,D>);
+
+/// Adds all vertices and edges of `src` to `dst`, cloning the labels. See `copy_graph_with`.
+pub fn copy_graph<'a,'b,V: Clone,E: Clone,G,H>(src: &'a G, dst: &mut H) -> Result<DescriptorMaps<G::Vertex,H::Vertex,G::Edge,H::Edge>,GraphError>
+    where G: 'a + VertexListGraph<'a,V,E> + EdgeListGraph<'a,V,E>, H: MutableGraph<'b,V,E> {
+    return copy_graph_with(src,dst,|_,lb| lb.clone(),|_,lb| lb.clone());
+}
+
+/// Adds all vertices and edges of `src` to `dst`. The labels of the copies are computed by
+/// `vertex_label` and `edge_label` from the descriptor and label of the original.
+///
+/// Returns maps from the descriptors of `src` to the ones of `dst`. Vertices and edges are added
+/// in the order `vertices()` and `edges()` return them. If `dst` rejects an edge the copy stops
+/// with the error, leaving the elements added so far in `dst`. If `dst` merges parallel edges,
+/// the merged edges map to the same descriptor.
+pub fn copy_graph_with<'a,'b,V,E,V2,E2,G,H,FV,FE>(src: &'a G, dst: &mut H, mut vertex_label: FV, mut edge_label: FE) -> Result<DescriptorMaps<G::Vertex,H::Vertex,G::Edge,H::Edge>,GraphError>
+    where G: 'a + VertexListGraph<'a,V,E> + EdgeListGraph<'a,V,E>, H: MutableGraph<'b,V2,E2>,
+          FV: FnMut(G::Vertex,&V) -> V2, FE: FnMut(G::Edge,&E) -> E2 {
+    let mut vertices = HashMap::with_capacity(src.num_vertices());
+    let mut edges = HashMap::with_capacity(src.num_edges());
+
+    for v in src.vertices() {
+        let lb = vertex_label(v,src.try_vertex_label(v)?);
+
+        vertices.insert(v,dst.add_vertex(lb));
+    }
+
+    for e in src.edges() {
+        let from = *vertices.get(&src.source(e)).ok_or(GraphError::UnknownVertex)?;
+        let to = *vertices.get(&src.target(e)).ok_or(GraphError::UnknownVertex)?;
+        let lb = edge_label(e,src.try_edge_label(e)?);
+
+        edges.insert(e,dst.try_add_edge(lb,from,to)?);
+    }
+
+    return Ok((vertices,edges));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adjacency_list::AdjacencyList;
+    use adjacency_matrix::{
+        AdjacencyMatrix,
+        OwnedAdjacencyMatrix,
+    };
+    use view::Filtered;
+    use traits::{
+        Graph,
+        IncidenceGraph,
+    };
+
+    #[test]
+    fn matrix_to_list_and_back() {
+        let row1 = [None,Some(12),Some(13)];
+        let row2 = [None,None,Some(23)];
+        let row3 = [Some(31),None,None];
+        let rows: [&[Option<usize>]; 3] = [&row1,&row2,&row3];
+        let labels = [1,2,3];
+        let mat = AdjacencyMatrix::new(&rows,&labels);
+
+        let mut list = AdjacencyList::<usize,usize>::new();
+        let (vm,em) = copy_graph(&mat,&mut list).unwrap();
+
+        assert_eq!(vm.len(), 3);
+        assert_eq!(em.len(), 4);
+        assert_eq!(list.num_edges(), 4);
+
+        for e in mat.edges() {
+            let f = em[&e];
+
+            assert_eq!(list.edge_label(f), mat.edge_label(e));
+            assert_eq!(list.source(f), vm[&mat.source(e)]);
+            assert_eq!(list.target(f), vm[&mat.target(e)]);
+        }
+
+        let mut owned = OwnedAdjacencyMatrix::<String,usize>::new();
+        let (vm2,_) = copy_graph_with(&list,&mut owned,|_,lb| format!("v{}",lb),|_,&lb| lb * 10).unwrap();
+        let v1 = vm2[&vm[&mat.vertices().next().unwrap()]];
+
+        assert_eq!(owned.vertex_label(v1), Some(&"v1".to_string()));
+        assert_eq!(owned.out_edges(v1).map(|e| *owned.edge_label(e).unwrap()).collect::<Vec<_>>(), vec![120,130]);
+    }
+
+    #[test]
+    fn copy_view() {
+        let mut g = AdjacencyList::<usize,()>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+
+        g.add_edge((),v1,v2);
+        g.add_edge((),v2,v3);
+        g.add_edge((),v3,v3);
+
+        let view = Filtered::new(&g,|v| v != v1,|_| true);
+        let mut h = AdjacencyList::<usize,()>::new();
+        let (vm,em) = copy_graph(&view,&mut h).unwrap();
+
+        assert_eq!(h.num_vertices(), 2);
+        assert_eq!(h.num_edges(), 2);
+        assert!(!vm.contains_key(&v1));
+
+        // map results on the copy back to the original
+        let back = vm.iter().map(|(&a,&b)| (b,a)).collect::<HashMap<_,_>>();
+        let succ = h.out_edges(vm[&v2]).map(|e| back[&h.target(e)]).collect::<Vec<_>>();
+
+        assert_eq!(succ, vec![v3]);
+        assert!(em.keys().all(|&e| g.source(e) != v1));
+    }
+}
@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::ops::Range;
+use std::slice::Iter;
+
+use adjacency_list::AdjacencyList;
+use traits::*;
+
+#[derive(PartialEq,Eq,Hash,Copy,Clone,Debug,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct CsrVertexDescriptor(pub usize);
+
+#[derive(PartialEq,Eq,Hash,Copy,Clone,Debug,PartialOrd,Ord,Serialize,Deserialize)]
+pub struct CsrEdgeDescriptor(pub usize);
+
+/// Immutable graph in compressed sparse row format.
+///
+/// The out edges of vertex `v` are the edges `out_offsets[v]..out_offsets[v + 1]`, their targets
+/// are stored in one contiguous array. In edges are kept in a second, reversed CSR that indexes
+/// into the same edge array. No operation hashes.
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(try_from = "SerializedCsr<V,E>")]
+pub struct CompressedSparseRow<V,E> {
+    vertex_labels: Vec<V>,
+    edge_labels: Vec<E>,
+    out_offsets: Vec<usize>,
+    sources: Vec<usize>,
+    targets: Vec<usize>,
+    in_offsets: Vec<usize>,
+    in_edges: Vec<CsrEdgeDescriptor>,
+}
+
+/// `CompressedSparseRow` as read, before its offsets and indices are checked.
+#[derive(Deserialize)]
+struct SerializedCsr<V,E> {
+    vertex_labels: Vec<V>,
+    edge_labels: Vec<E>,
+    out_offsets: Vec<usize>,
+    sources: Vec<usize>,
+    targets: Vec<usize>,
+    in_offsets: Vec<usize>,
+    in_edges: Vec<CsrEdgeDescriptor>,
+}
+
+/// Checks that `offsets` split `0..m` into `n` ascending ranges.
+fn check_offsets(offsets: &[usize], n: usize, m: usize) -> Result<(),String> {
+    if offsets.len() != n + 1 || offsets[0] != 0 || offsets[n] != m {
+        return Err("offsets don't cover the edges".to_string());
+    }
+
+    if offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err("offsets decrease".to_string());
+    }
+
+    return Ok(());
+}
+
+impl<V,E> TryFrom<SerializedCsr<V,E>> for CompressedSparseRow<V,E> {
+    type Error = String;
+
+    fn try_from(g: SerializedCsr<V,E>) -> Result<Self,String> {
+        let n = g.vertex_labels.len();
+        let m = g.edge_labels.len();
+
+        if g.sources.len() != m || g.targets.len() != m || g.in_edges.len() != m {
+            return Err(format!("expected {} edges",m));
+        }
+
+        check_offsets(&g.out_offsets,n,m)?;
+        check_offsets(&g.in_offsets,n,m)?;
+
+        if g.targets.iter().any(|&to| to >= n) {
+            return Err("target out of bounds".to_string());
+        }
+
+        if (0..n).any(|v| g.sources[g.out_offsets[v]..g.out_offsets[v + 1]].iter().any(|&from| from != v)) {
+            return Err("sources don't match the out offsets".to_string());
+        }
+
+        // every edge once, in the range of its target
+        let mut seen = vec![false; m];
+
+        for v in 0..n {
+            for &CsrEdgeDescriptor(e) in g.in_edges[g.in_offsets[v]..g.in_offsets[v + 1]].iter() {
+                if e >= m || g.targets[e] != v || seen[e] {
+                    return Err("in edges don't match the targets".to_string());
+                }
+
+                seen[e] = true;
+            }
+        }
+
+        return Ok(CompressedSparseRow{
+            vertex_labels: g.vertex_labels,
+            edge_labels: g.edge_labels,
+            out_offsets: g.out_offsets,
+            sources: g.sources,
+            targets: g.targets,
+            in_offsets: g.in_offsets,
+            in_edges: g.in_edges,
+        });
+    }
+}
+
+impl<V,E> CompressedSparseRow<V,E> {
+    /// Builds a graph with vertices `0..vertices.len()` and the given `(from, to, label)` edges.
+    /// Edges are numbered by source vertex, edges with the same source keep their relative order.
+    /// Returns `None` if an edge refers to a vertex that does not exist.
+    pub fn from_edges(vertices: Vec<V>, edges: Vec<(usize,usize,E)>) -> Option<Self> {
+        let n = vertices.len();
+
+        if edges.iter().any(|&(from,to,_)| from >= n || to >= n) {
+            return None;
+        }
+
+        let mut out_offsets = vec![0; n + 1];
+        let mut in_offsets = vec![0; n + 1];
+
+        for &(from,to,_) in edges.iter() {
+            out_offsets[from + 1] += 1;
+            in_offsets[to + 1] += 1;
+        }
+
+        for i in 0..n {
+            out_offsets[i + 1] += out_offsets[i];
+            in_offsets[i + 1] += in_offsets[i];
+        }
+
+        let mut slots = (0..edges.len()).map(|_| None).collect::<Vec<Option<(usize,usize,E)>>>();
+        let mut next = out_offsets.clone();
+
+        for (from,to,lb) in edges {
+            slots[next[from]] = Some((from,to,lb));
+            next[from] += 1;
+        }
+
+        let mut edge_labels = Vec::with_capacity(slots.len());
+        let mut sources = Vec::with_capacity(slots.len());
+        let mut targets = Vec::with_capacity(slots.len());
+
+        for (from,to,lb) in slots.into_iter().map(Option::unwrap) {
+            sources.push(from);
+            targets.push(to);
+            edge_labels.push(lb);
+        }
+
+        let mut in_edges = vec![CsrEdgeDescriptor(0); targets.len()];
+        let mut next = in_offsets.clone();
+
+        for (e,&to) in targets.iter().enumerate() {
+            in_edges[next[to]] = CsrEdgeDescriptor(e);
+            next[to] += 1;
+        }
+
+        return Some(CompressedSparseRow{
+            vertex_labels: vertices,
+            edge_labels: edge_labels,
+            out_offsets: out_offsets,
+            sources: sources,
+            targets: targets,
+            in_offsets: in_offsets,
+            in_edges: in_edges,
+        });
+    }
+}
+
+impl<V: Clone,E: Clone> CompressedSparseRow<V,E> {
+    /// Freezes `graph` into a CSR graph. Vertices are numbered in the order `graph.vertices()`
+    /// returns them. The returned map translates the vertex descriptors of `graph` into the ones
+    /// of the new graph.
+    pub fn from_graph<'a,G: 'a + VertexListGraph<'a,V,E> + EdgeListGraph<'a,V,E>>(graph: &'a G) -> (Self,HashMap<G::Vertex,CsrVertexDescriptor>) {
+        let vertex_idx = HashMap::<G::Vertex,usize>::from_iter(graph.vertices().enumerate().map(|(a,b)| (b,a)));
+        let vertices = graph.vertices().map(|v| graph.vertex_label(v).unwrap().clone()).collect::<Vec<_>>();
+        let edges = graph.edges().map(|e| {
+            (vertex_idx[&graph.source(e)],vertex_idx[&graph.target(e)],graph.edge_label(e).unwrap().clone())
+        }).collect::<Vec<_>>();
+        let ret = Self::from_edges(vertices,edges).unwrap();
+        let map = vertex_idx.into_iter().map(|(v,i)| (v,CsrVertexDescriptor(i))).collect();
+
+        return (ret,map);
+    }
+}
+
+impl<'a,V: Clone,E: Clone> From<&'a AdjacencyList<V,E>> for CompressedSparseRow<V,E> {
+    fn from(graph: &'a AdjacencyList<V,E>) -> Self {
+        return Self::from_graph(graph).0;
+    }
+}
+
+impl<'a,V,E> Graph<'a,V,E> for CompressedSparseRow<V,E> {
+    type Vertex = CsrVertexDescriptor;
+    type Edge = CsrEdgeDescriptor;
+
+    fn edge_label(&self, e: Self::Edge) -> Option<&E> {
+        return self.edge_labels.get(e.0);
+    }
+
+    fn vertex_label(&self, v: Self::Vertex) -> Option<&V> {
+        return self.vertex_labels.get(v.0);
+    }
+
+    fn source(&self, e: Self::Edge) -> Self::Vertex {
+        return CsrVertexDescriptor(self.sources[e.0]);
+    }
+
+    fn target(&self, e: Self::Edge) -> Self::Vertex {
+        return CsrVertexDescriptor(self.targets[e.0]);
+    }
+}
+
+/// Out edges are a contiguous range of edge descriptors, in edges a slice of the reverse CSR.
+#[derive(Debug)]
+pub enum CsrIncidence<'a> {
+    Out(Range<usize>),
+    In(Iter<'a,CsrEdgeDescriptor>),
+}
+
+impl<'a> Iterator for CsrIncidence<'a> {
+    type Item = CsrEdgeDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self {
+            CsrIncidence::Out(ref mut r) => r.next().map(CsrEdgeDescriptor),
+            CsrIncidence::In(ref mut i) => i.next().cloned(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        match *self {
+            CsrIncidence::Out(ref r) => r.size_hint(),
+            CsrIncidence::In(ref i) => i.size_hint(),
+        }
+    }
+}
+
+impl<'a,V,E> IncidenceGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    type Incidence = CsrIncidence<'a>;
+
+    fn out_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.out_offsets[v.0 + 1] - self.out_offsets[v.0];
+    }
+
+    fn out_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return CsrIncidence::Out(self.out_offsets[v.0]..self.out_offsets[v.0 + 1]);
+    }
+}
+
+impl<'a,V,E> BidirectionalGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    fn in_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.in_offsets[v.0 + 1] - self.in_offsets[v.0];
+    }
+
+    fn degree(&'a self, v: Self::Vertex) -> usize {
+        return self.in_degree(v) + self.out_degree(v);
+    }
+
+    fn in_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return CsrIncidence::In(self.in_edges[self.in_offsets[v.0]..self.in_offsets[v.0 + 1]].iter());
+    }
+}
+
+#[derive(Debug)]
+pub struct CsrAdjacency {
+    adj: Vec<CsrVertexDescriptor>
+}
+
+impl Iterator for CsrAdjacency {
+    type Item = CsrVertexDescriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.adj.pop();
+    }
+}
+
+impl<'a,V,E> AdjacencyGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    type Adjacency = CsrAdjacency;
+
+    fn adjacent_vertices(&'a self, v: Self::Vertex) -> Self::Adjacency {
+        let i = self.out_edges(v).map(|x| return self.target(x));
+        let o = self.in_edges(v).map(|x| return self.source(x));
+        let mut raw = i.chain(o).collect::<Vec<CsrVertexDescriptor>>();
+
+        raw.sort();
+        raw.dedup();
+
+        return CsrAdjacency { adj: raw };
+    }
+}
+
+impl<'a,V,E> VertexListGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    type Vertices = std::iter::Map<Range<usize>,fn(usize) -> CsrVertexDescriptor>;
+
+    fn vertices(&'a self) -> Self::Vertices {
+        return (0..self.vertex_labels.len()).map(CsrVertexDescriptor);
+    }
+
+    fn num_vertices(&self) -> usize {
+        return self.vertex_labels.len();
+    }
+}
+
+impl<'a,V,E> EdgeListGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    type Edges = std::iter::Map<Range<usize>,fn(usize) -> CsrEdgeDescriptor>;
+
+    fn num_edges(&self) -> usize {
+        return self.edge_labels.len();
+    }
+
+    fn edges(&'a self) -> Self::Edges {
+        return (0..self.edge_labels.len()).map(CsrEdgeDescriptor);
+    }
+}
+
+impl<'a,V,E> VertexIndexGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return v.0;
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.vertex_labels.len();
+    }
+}
+
+impl<'a,V,E> EdgeIndexGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return e.0;
+    }
+
+    fn edge_index_bound(&self) -> usize {
+        return self.edge_labels.len();
+    }
+}
+
+impl<'a,V,E> AdjacencyMatrixGraph<'a,V,E> for CompressedSparseRow<V,E> {
+    fn edge(&'a self, from: Self::Vertex, to: Self::Vertex) -> Option<Self::Edge> {
+        return self.out_edges(from).find(|&e| self.targets[e.0] == to.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dominator::{
+        dominators,
+        immediate_dominator,
+    };
+
+    #[test]
+    fn from_edges() {
+        let g = CompressedSparseRow::from_edges(vec!["a","b","c"],vec![(1,2,"y"),(0,1,"x"),(1,0,"z"),(2,2,"w")]).unwrap();
+        let (a,b,c) = (CsrVertexDescriptor(0),CsrVertexDescriptor(1),CsrVertexDescriptor(2));
+
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 4);
+        assert_eq!(g.vertex_label(b), Some(&"b"));
+
+        let out = g.out_edges(b).map(|e| *g.edge_label(e).unwrap()).collect::<Vec<_>>();
+        assert_eq!(out, vec!["y","z"]);
+
+        let inc = g.in_edges(c).map(|e| *g.edge_label(e).unwrap()).collect::<Vec<_>>();
+        assert_eq!(inc, vec!["y","w"]);
+
+        assert_eq!(g.out_degree(a), 1);
+        assert_eq!(g.in_degree(a), 1);
+        assert_eq!(g.degree(c), 3);
+        assert_eq!(g.adjacent_vertices(a).collect::<Vec<_>>(), vec![b]);
+
+        let e = g.edge(c,c).unwrap();
+        assert_eq!(g.source(e), c);
+        assert_eq!(g.target(e), c);
+        assert_eq!(g.edge(a,c), None);
+
+        assert!(CompressedSparseRow::from_edges(vec![()],vec![(0,1,())]).is_none());
+    }
+
+    #[test]
+    fn serialize() {
+        use serde_json;
+
+        let g = CompressedSparseRow::from_edges(vec![1,2,3],vec![(1,2,12),(0,1,1),(2,2,22)]).unwrap();
+        let h: CompressedSparseRow<i32,i32> = serde_json::from_str(&serde_json::to_string(&g).unwrap()).unwrap();
+
+        assert_eq!(h.edges().map(|e| (h.source(e),h.target(e))).collect::<Vec<_>>(), g.edges().map(|e| (g.source(e),g.target(e))).collect::<Vec<_>>());
+        assert_eq!(h.in_edges(CsrVertexDescriptor(2)).collect::<Vec<_>>(), g.in_edges(CsrVertexDescriptor(2)).collect::<Vec<_>>());
+
+        let load = |out_offsets: &str, targets: &str, in_offsets: &str, in_edges: &str| {
+            let json = format!("{{\"vertex_labels\":[1,2],\"edge_labels\":[1],\"out_offsets\":{},\"sources\":[0],\"targets\":{},\"in_offsets\":{},\"in_edges\":{}}}",out_offsets,targets,in_offsets,in_edges);
+            serde_json::from_str::<CompressedSparseRow<i32,i32>>(&json).err().map(|e| e.to_string())
+        };
+
+        assert_eq!(load("[0,1,1]","[1]","[0,0,1]","[0]"), None);
+        assert_eq!(load("[0,1]","[1]","[0,0,1]","[0]"), Some("offsets don't cover the edges".to_string()));
+        assert_eq!(load("[0,2,1]","[1]","[0,0,1]","[0]"), Some("offsets decrease".to_string()));
+        assert_eq!(load("[0,1,1]","[2]","[0,0,1]","[0]"), Some("target out of bounds".to_string()));
+        assert_eq!(load("[0,0,1]","[1]","[0,0,1]","[0]"), Some("sources don't match the out offsets".to_string()));
+        assert_eq!(load("[0,1,1]","[1]","[0,1,1]","[0]"), Some("in edges don't match the targets".to_string()));
+        assert_eq!(load("[0,1,1]","[1]","[0,0,1]","[5]"), Some("in edges don't match the targets".to_string()));
+    }
+
+    #[test]
+    fn from_adjacency_list() {
+        let mut g = AdjacencyList::<usize,usize>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+        let v5 = g.add_vertex(5);
+        let v6 = g.add_vertex(6);
+
+        g.add_edge(12,v1,v2);
+        g.add_edge(23,v2,v3);
+        g.add_edge(24,v2,v4);
+        g.add_edge(26,v2,v6);
+        g.add_edge(35,v3,v5);
+        g.add_edge(45,v4,v5);
+        g.add_edge(52,v5,v2);
+
+        let (csr,map) = CompressedSparseRow::from_graph(&g);
+
+        assert_eq!(csr.num_vertices(), g.num_vertices());
+        assert_eq!(csr.num_edges(), g.num_edges());
+
+        for v in g.vertices() {
+            let w = map[&v];
+
+            assert_eq!(g.vertex_label(v), csr.vertex_label(w));
+            assert_eq!(g.out_degree(v), csr.out_degree(w));
+            assert_eq!(g.in_degree(v), csr.in_degree(w));
+        }
+
+        for e in csr.edges() {
+            let l = *csr.edge_label(e).unwrap();
+            let from = *csr.vertex_label(csr.source(e)).unwrap();
+            let to = *csr.vertex_label(csr.target(e)).unwrap();
+
+            assert_eq!(l, from * 10 + to);
+        }
+
+        let dom1 = dominators(v1,&g);
+        let dom2 = dominators(map[&v1],&csr);
+        let idom1 = immediate_dominator(v1,&g);
+        let idom2 = immediate_dominator(map[&v1],&csr);
+
+        for v in g.vertices() {
+            let mut d1 = dom1[&v].iter().map(|&x| map[&x]).collect::<Vec<_>>();
+
+            d1.sort();
+            assert_eq!(d1, dom2[&map[&v]]);
+            assert_eq!(map[&idom1[&v]], idom2[&map[&v]]);
+        }
+
+        let csr2 = CompressedSparseRow::from(&g);
+        assert_eq!(csr2.num_edges(), 7);
+    }
+}
@@ -9,31 +9,54 @@ use search::{
 use traits::{
     Graph,
     VertexListGraph,
+    VertexIndexGraph,
     BidirectionalGraph,
 };
+use property_map::{
+    MutablePropertyMap,
+    VertexVecPropertyMap,
+};
 use bit_set::BitSet;
 
 pub fn dominators<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E>>(start: G::Vertex, graph: &'a G) -> HashMap<G::Vertex,Vec<G::Vertex>> {
     let vertex_idx = HashMap::<G::Vertex,usize>::from_iter(graph.vertices().enumerate().map(|(a,b)| (b,a)));
-    let vertex_ridx = HashMap::<usize,G::Vertex>::from_iter(graph.vertices().enumerate());
+
+    return dominators_with(start,graph,vertex_idx.len(),|vx| vertex_idx[&vx]);
+}
+
+/// `dominators` numbering the vertices with `vertex_index` instead of a hash map.
+pub fn dominators_indexed<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E> + VertexIndexGraph<'a,V,E>>(start: G::Vertex, graph: &'a G) -> HashMap<G::Vertex,Vec<G::Vertex>> {
+    return dominators_with(start,graph,graph.vertex_index_bound(),|vx| graph.vertex_index(vx));
+}
+
+/// Dominator sets computed on the vertex numbers `index`, which are smaller than `bound`.
+fn dominators_with<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E>, F: Fn(G::Vertex) -> usize>(start: G::Vertex, graph: &'a G, bound: usize, index: F) -> HashMap<G::Vertex,Vec<G::Vertex>> {
+    let mut vertex_ridx = vec![None; bound];
     let mut fixpoint = false;
-    let mut all_set = BitSet::new();
+    let mut all_set = BitSet::with_capacity(bound);
+
+    for vx in graph.vertices() {
+        let vx_idx = index(vx);
 
-    for vx in 0..vertex_idx.len() {
-        all_set.insert(vx);
+        vertex_ridx[vx_idx] = Some(vx);
+        all_set.insert(vx_idx);
     }
 
-    let mut cur_dom = vec![all_set; vertex_idx.len()];
+    let mut cur_dom = vec![BitSet::new(); bound];
+
+    for vx in graph.vertices() {
+        cur_dom[index(vx)] = all_set.clone();
+    }
 
     while !fixpoint {
-        let mut next_dom = vec![BitSet::new(); vertex_idx.len()];
+        let mut next_dom = vec![BitSet::new(); bound];
 
         for vx in graph.vertices() {
             let mut my_dom: Option<BitSet> = None;
 
             if vx != start {
                 for e in graph.in_edges(vx) {
-                    let prev = &cur_dom[vertex_idx[&graph.source(e)]];
+                    let prev = &cur_dom[index(graph.opposite(e,vx))];
 
                     if let Some(ref mut s) = my_dom {
                         s.intersect_with(&prev);
@@ -44,7 +67,7 @@ pub fn dominators<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> +
             }
 
             let mut final_dom = my_dom.unwrap_or(BitSet::new());
-            let vx_idx = vertex_idx[&vx];
+            let vx_idx = index(vx);
 
             final_dom.insert(vx_idx);
             next_dom[vx_idx] = final_dom;
@@ -55,11 +78,11 @@ pub fn dominators<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> +
     }
 
     let mut ret = HashMap::<G::Vertex,Vec<G::Vertex>>::new();
-    for (vx,idx) in vertex_idx.iter() {
-        let mut res = cur_dom[*idx].iter().map(|a| vertex_ridx[&a]).collect::<Vec<G::Vertex>>();
+    for vx in graph.vertices() {
+        let mut res = cur_dom[index(vx)].iter().map(|a| vertex_ridx[a].unwrap()).collect::<Vec<G::Vertex>>();
 
         res.sort();
-        ret.insert(*vx,res);
+        ret.insert(vx,res);
     }
 
     ret
@@ -72,7 +95,7 @@ pub fn dominance_frontiers<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<
     for b in graph.vertices() {
         let pred = {
             let mut ret = graph.in_edges(b)
-                               .map(|e| graph.source(e))
+                               .map(|e| graph.opposite(e,b))
                                .filter(|&x| x != b)
                                .collect::<Vec<G::Vertex>>();
             ret.sort();
@@ -103,27 +126,44 @@ pub fn dominance_frontiers<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<
 /// Cooper, Harvey, Kennedy: "A Simple, Fast Dominance Algorithm"
 pub fn immediate_dominator<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E>>(start: G::Vertex, graph: &'a G) -> HashMap<G::Vertex,G::Vertex> {
     let postorder = TreeIterator::new(start,TraversalOrder::Postorder,graph).collect::<Vec<_>>();
-    let po_idx = HashMap::<G::Vertex,usize>::from_iter(postorder.iter().enumerate().map(|(a,b)| (b.clone(),a)));
-    fn intersect<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E>>(b1: G::Vertex,b2: G::Vertex,po_idx: &HashMap<G::Vertex,usize>, postorder: &Vec<G::Vertex>, ret: &HashMap<G::Vertex,G::Vertex> ) -> G::Vertex {
-        let mut f1 = po_idx[&b1];
-        let mut f2 = po_idx[&b2];
+
+    return immediate_dominator_with(start,graph,postorder,HashMap::new(),HashMap::new());
+}
+
+/// `immediate_dominator` keeping the per-vertex state in vectors indexed by `vertex_index`.
+pub fn immediate_dominator_indexed<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E> + VertexIndexGraph<'a,V,E>>(start: G::Vertex, graph: &'a G) -> HashMap<G::Vertex,G::Vertex> {
+    let postorder = TreeIterator::new_indexed(start,TraversalOrder::Postorder,graph).collect::<Vec<_>>();
+
+    return immediate_dominator_with(start,graph,postorder,VertexVecPropertyMap::new(graph),VertexVecPropertyMap::new(graph));
+}
+
+/// Immediate dominators of the vertices in `postorder`, using `po_idx` for their position in it
+/// and `idom` for the dominators found so far.
+fn immediate_dominator_with<'a, V, E, G, P, D>(start: G::Vertex, graph: &'a G, postorder: Vec<G::Vertex>, mut po_idx: P, mut idom: D) -> HashMap<G::Vertex,G::Vertex>
+    where G: 'a + Graph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E>, P: MutablePropertyMap<G::Vertex,Value=usize>, D: MutablePropertyMap<G::Vertex,Value=G::Vertex> {
+    for (a,b) in postorder.iter().enumerate() {
+        po_idx.put(*b,a);
+    }
+
+    fn intersect<Vx: Copy, P: MutablePropertyMap<Vx,Value=usize>, D: MutablePropertyMap<Vx,Value=Vx>>(b1: Vx,b2: Vx,po_idx: &P, postorder: &[Vx], idom: &D) -> Vx {
+        let mut f1 = po_idx.get(b1).unwrap();
+        let mut f2 = po_idx.get(b2).unwrap();
 
         while f1 != f2 {
             while f1 < f2 {
-                f1 = po_idx[&ret[&postorder[f1]]];
+                f1 = po_idx.get(idom.get(postorder[f1]).unwrap()).unwrap();
             }
             while f2 < f1 {
-                f2 = po_idx[&ret[&postorder[f2]]];
+                f2 = po_idx.get(idom.get(postorder[f2]).unwrap()).unwrap();
             }
         }
 
         postorder[f1]
-    };
+    }
 
-    let mut ret = HashMap::<G::Vertex,G::Vertex>::new();
     let mut fixpoint = false;
 
-    ret.insert(start,start);
+    idom.put(start,start);
 
     while !fixpoint {
         fixpoint = true;
@@ -131,29 +171,29 @@ pub fn immediate_dominator<'a, V, E, G: 'a + Graph<'a,V,E> + BidirectionalGraph<
         for b in postorder.iter().rev().filter(|&&v| v != start) {
             let pred = {
                 let mut ret = graph.in_edges(*b)
-                                   .map(|e| graph.source(e))
+                                   .map(|e| graph.opposite(e,*b))
                                    .filter(|&x| x != *b)
                                    .collect::<Vec<G::Vertex>>();
                 ret.sort();
                 ret.dedup();
                 ret
             };
-            let mut new_idom = *pred.iter().find(|&x| ret.contains_key(x)).unwrap();
+            let mut new_idom = *pred.iter().find(|&&x| idom.get(x).is_some()).unwrap();
 
             for p in pred.iter().filter(move |&&x| x != new_idom) {
-                if ret.contains_key(&p) {
-                    new_idom = intersect::<V,E,G>(*p,new_idom,&po_idx,&postorder,&ret);
+                if idom.get(*p).is_some() {
+                    new_idom = intersect(*p,new_idom,&po_idx,&postorder,&idom);
                 }
             }
 
-            if ret.get(b) != Some(&new_idom) {
-                ret.insert(*b,new_idom);
+            if idom.get(*b) != Some(new_idom) {
+                idom.put(*b,new_idom);
                 fixpoint = false;
             }
         }
     }
 
-    ret
+    HashMap::<G::Vertex,G::Vertex>::from_iter(postorder.iter().filter_map(|&v| idom.get(v).map(|d| (v,d))))
 }
 
 #[cfg(test)]
@@ -195,6 +235,38 @@ mod tests {
         assert_eq!(dom[&v6], vec![v1,v2,v6]);
     }
 
+    #[test]
+    fn dom_sparse_indices() {
+        let mut g = AdjacencyList::<usize,()>::new();
+        let v0 = g.add_vertex(0);
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+
+        g.add_edge((),v1,v2);
+        g.add_edge((),v1,v4);
+        g.add_edge((),v2,v4);
+        g.add_edge((),v0,v3);
+        g.remove_vertex(v0);
+        g.remove_vertex(v3);
+
+        let dom = dominators_indexed(v1,&g);
+
+        assert_eq!(dom.len(), 3);
+        assert_eq!(dom[&v1], vec![v1]);
+        assert_eq!(dom[&v2], vec![v1,v2]);
+        assert_eq!(dom[&v4], vec![v1,v4]);
+        assert_eq!(dominators(v1,&g), dom);
+
+        let idom = immediate_dominator_indexed(v1,&g);
+
+        assert_eq!(idom.len(), 3);
+        assert_eq!(idom[&v2], v1);
+        assert_eq!(idom[&v4], v1);
+        assert_eq!(immediate_dominator(v1,&g), idom);
+    }
+
     #[test]
     fn idom() {
         let mut g = AdjacencyList::<usize,()>::new();
@@ -0,0 +1,1018 @@
+//! Graphviz DOT input and output.
+//!
+//! `DotWriter` renders any `VertexListGraph + EdgeListGraph`. Vertex and edge attributes are
+//! computed by callbacks, vertices can be grouped into (nested) clusters. `parse_dot` reads DOT
+//! into an `AdjacencyList`.
+
+use std::collections::{HashMap,HashSet};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use adjacency_list::{
+    AdjacencyList,
+    AdjacencyListVertexDescriptor,
+};
+use order::HierarchicalOrdering;
+use traits::{
+    Graph,
+    VertexListGraph,
+    EdgeListGraph,
+    MutableGraph,
+    Direction,
+};
+
+/// DOT attributes as name/value pairs.
+pub type Attributes = Vec<(String,String)>;
+
+/// Group of vertices drawn as a `subgraph cluster_*`.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Cluster<Vx> {
+    pub attributes: Attributes,
+    pub vertices: Vec<Vx>,
+    pub clusters: Vec<Cluster<Vx>>,
+}
+
+impl<Vx: Clone> Cluster<Vx> {
+    pub fn new(vertices: Vec<Vx>) -> Self {
+        return Cluster{
+            attributes: vec![],
+            vertices: vertices,
+            clusters: vec![],
+        };
+    }
+
+    /// One cluster per component of a weak topological order, nested like the components. The
+    /// elements of the outermost level are not clustered.
+    pub fn from_ordering(ordering: &HierarchicalOrdering<Vx>) -> Vec<Self> {
+        match *ordering {
+            HierarchicalOrdering::Element(_) => return vec![],
+            HierarchicalOrdering::Component(ref comps) => {
+                return comps.iter().filter_map(|c| Self::from_component(c)).collect();
+            }
+        }
+    }
+
+    fn from_component(ordering: &HierarchicalOrdering<Vx>) -> Option<Self> {
+        match *ordering {
+            HierarchicalOrdering::Element(_) => return None,
+            HierarchicalOrdering::Component(ref comps) => {
+                let mut ret = Cluster::new(vec![]);
+
+                for c in comps.iter() {
+                    match **c {
+                        HierarchicalOrdering::Element(ref v) => ret.vertices.push(v.clone()),
+                        HierarchicalOrdering::Component(_) => ret.clusters.extend(Self::from_component(c)),
+                    }
+                }
+
+                return Some(ret);
+            }
+        }
+    }
+}
+
+/// Writes a graph in DOT format. Vertices are named `n0`, `n1`, ... in `vertices()` order.
+pub struct DotWriter<'a,V,E,G: 'a + Graph<'a,V,E>> {
+    graph: &'a G,
+    directed: bool,
+    graph_attributes: Attributes,
+    vertex_attributes: Option<Box<Fn(G::Vertex,&V) -> Attributes + 'a>>,
+    edge_attributes: Option<Box<Fn(G::Edge,&E) -> Attributes + 'a>>,
+    clusters: Vec<Cluster<G::Vertex>>,
+    _marker: PhantomData<(V,E)>,
+}
+
+impl<'a,V,E,G: 'a + VertexListGraph<'a,V,E> + EdgeListGraph<'a,V,E>> DotWriter<'a,V,E,G> {
+    pub fn new(graph: &'a G) -> Self {
+        return DotWriter{
+            graph: graph,
+            directed: true,
+            graph_attributes: vec![],
+            vertex_attributes: None,
+            edge_attributes: None,
+            clusters: vec![],
+            _marker: PhantomData,
+        };
+    }
+
+    /// Writes a `graph` with `--` edges instead of a `digraph`.
+    pub fn undirected(mut self) -> Self {
+        self.directed = false;
+        return self;
+    }
+
+    /// Adds an attribute of the whole graph, e.g. `rankdir`.
+    pub fn graph_attribute(mut self, name: &str, value: &str) -> Self {
+        self.graph_attributes.push((name.to_string(),value.to_string()));
+        return self;
+    }
+
+    /// Sets the function computing the attributes of each vertex, e.g. `label` or `shape`.
+    pub fn vertex_attributes<F: Fn(G::Vertex,&V) -> Attributes + 'a>(mut self, f: F) -> Self {
+        self.vertex_attributes = Some(Box::new(f));
+        return self;
+    }
+
+    /// Sets the function computing the attributes of each edge.
+    pub fn edge_attributes<F: Fn(G::Edge,&E) -> Attributes + 'a>(mut self, f: F) -> Self {
+        self.edge_attributes = Some(Box::new(f));
+        return self;
+    }
+
+    /// Draws the vertices of `cluster` in a box. Vertices should be part of at most one cluster.
+    pub fn cluster(mut self, cluster: Cluster<G::Vertex>) -> Self {
+        self.clusters.push(cluster);
+        return self;
+    }
+
+    pub fn write<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        return write!(out,"{}",self);
+    }
+
+    fn write_vertex(&self, f: &mut fmt::Formatter, v: G::Vertex, id: usize, indent: usize) -> fmt::Result {
+        let attrs = match (&self.vertex_attributes,self.graph.vertex_label(v)) {
+            (Some(func),Some(lb)) => func(v,lb),
+            _ => vec![],
+        };
+
+        write!(f,"{:indent$}n{}","",id,indent = indent)?;
+        write_attributes(f,&attrs)?;
+        return writeln!(f,";");
+    }
+
+    fn write_cluster(&self, f: &mut fmt::Formatter, cluster: &Cluster<G::Vertex>, ids: &HashMap<G::Vertex,usize>, num: &mut usize, indent: usize) -> fmt::Result {
+        writeln!(f,"{:indent$}subgraph cluster_{} {{","",*num,indent = indent)?;
+        *num += 1;
+
+        for (k,v) in cluster.attributes.iter() {
+            writeln!(f,"{:indent$}{}={};","",quote(k),quote(v),indent = indent + 4)?;
+        }
+
+        for v in cluster.vertices.iter() {
+            if let Some(&id) = ids.get(v) {
+                self.write_vertex(f,*v,id,indent + 4)?;
+            }
+        }
+
+        for c in cluster.clusters.iter() {
+            self.write_cluster(f,c,ids,num,indent + 4)?;
+        }
+
+        return writeln!(f,"{:indent$}}}","",indent = indent);
+    }
+}
+
+impl<'a,V,E,G: 'a + VertexListGraph<'a,V,E> + EdgeListGraph<'a,V,E>> fmt::Display for DotWriter<'a,V,E,G> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ids = self.graph.vertices().enumerate().map(|(i,v)| (v,i)).collect::<HashMap<_,_>>();
+        let mut clustered = HashSet::new();
+        let mut stack = self.clusters.iter().collect::<Vec<_>>();
+
+        while let Some(c) = stack.pop() {
+            clustered.extend(c.vertices.iter().cloned());
+            stack.extend(c.clusters.iter());
+        }
+
+        writeln!(f,"{} {{",if self.directed { "digraph" } else { "graph" })?;
+
+        for (k,v) in self.graph_attributes.iter() {
+            writeln!(f,"    {}={};",quote(k),quote(v))?;
+        }
+
+        for v in self.graph.vertices() {
+            if !clustered.contains(&v) {
+                self.write_vertex(f,v,ids[&v],4)?;
+            }
+        }
+
+        let mut num = 0;
+
+        for c in self.clusters.iter() {
+            self.write_cluster(f,c,&ids,&mut num,4)?;
+        }
+
+        for e in self.graph.edges() {
+            let from = ids[&self.graph.source(e)];
+            let to = ids[&self.graph.target(e)];
+            let attrs = match (&self.edge_attributes,self.graph.edge_label(e)) {
+                (Some(func),Some(lb)) => func(e,lb),
+                _ => vec![],
+            };
+
+            write!(f,"    n{} {} n{}",from,if self.directed { "->" } else { "--" },to)?;
+            write_attributes(f,&attrs)?;
+            writeln!(f,";")?;
+        }
+
+        return writeln!(f,"}}");
+    }
+}
+
+fn write_attributes(f: &mut fmt::Formatter, attrs: &Attributes) -> fmt::Result {
+    if attrs.is_empty() {
+        return Ok(());
+    }
+
+    let attrs = attrs.iter().map(|(k,v)| format!("{}={}",quote(k),quote(v))).collect::<Vec<_>>();
+    return write!(f," [{}]",attrs.join(","));
+}
+
+/// Double quoted DOT string. Backslashes starting the label escapes `\l`, `\r` and `\n` are kept,
+/// all others are doubled so that they can't escape the closing quote.
+fn quote(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    let mut chars = s.chars().peekable();
+
+    ret.push('"');
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\n' => ret.push_str("\\n"),
+            '\\' => match chars.peek() {
+                Some(&'l') | Some(&'r') | Some(&'n') => ret.push('\\'),
+                _ => ret.push_str("\\\\"),
+            },
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+
+    return ret;
+}
+
+/// Position and reason of a DOT syntax error. Lines and columns start at 1.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub struct DotError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f,"{}:{}: {}",self.line,self.column,self.message);
+    }
+}
+
+impl Error for DotError {}
+
+/// Parses a DOT `graph` or `digraph` into an `AdjacencyList`.
+///
+/// Supports node, edge and attribute statements, `a=b` statements, subgraphs (also as edge
+/// operands) and edge chains like `a -> b -> c`. Ports are ignored. The attributes of a vertex or
+/// edge, including the defaults set by `node [...]` and `edge [...]`, are passed to `vertex_label`
+/// resp. `edge_label`, the former together with the node ID. Graph attributes are ignored. Edges
+/// of an undirected `graph` are added in the order they are written, parse into an
+/// `AdjacencyList<V,E,UndirectedEdges>` to treat them as undirected. A `strict` graph gets at most
+/// one edge per pair of nodes, repeating an edge sets more attributes on it.
+///
+/// Returns the graph and a map from node IDs to vertices.
+pub fn parse_dot<V,E,D,FV,FE>(input: &str, mut vertex_label: FV, mut edge_label: FE) -> Result<(AdjacencyList<V,E,D>,HashMap<String,AdjacencyListVertexDescriptor>),DotError>
+    where D: Direction, FV: FnMut(&str,&HashMap<String,String>) -> V, FE: FnMut(&HashMap<String,String>) -> E {
+    let mut parser = Parser{
+        tokens: Lexer::new(input).tokenize()?,
+        pos: 0,
+        directed: true,
+        strict: false,
+        nodes: vec![],
+        node_ids: HashMap::new(),
+        edges: vec![],
+        edge_ids: HashMap::new(),
+    };
+
+    parser.graph()?;
+
+    let mut ret = AdjacencyList::<V,E,D>::empty(false);
+    let mut ids = HashMap::with_capacity(parser.nodes.len());
+    let mut vertices = Vec::with_capacity(parser.nodes.len());
+
+    ret.reserve(parser.nodes.len(),parser.edges.len());
+
+    for (id,attrs) in parser.nodes {
+        let v = ret.add_vertex(vertex_label(&id,&attrs));
+
+        vertices.push(v);
+        ids.insert(id,v);
+    }
+
+    for (from,to,attrs,(line,column)) in parser.edges {
+        if let Err(e) = ret.try_add_edge(edge_label(&attrs),vertices[from],vertices[to]) {
+            return Err(error(line,column,format!("edge not added: {}",e)));
+        }
+    }
+
+    return Ok((ret,ids));
+}
+
+#[derive(Clone,Debug,PartialEq)]
+enum Token {
+    /// Identifier, numeral or string. The flag is set for quoted and HTML strings, which are never
+    /// keywords.
+    Id(String,bool),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Semicolon,
+    Comma,
+    Colon,
+    Equal,
+    Plus,
+    Arrow,
+    Line,
+    End,
+}
+
+fn error(line: usize, column: usize, message: String) -> DotError {
+    return DotError{ line: line, column: column, message: message };
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    fn new(input: &str) -> Self {
+        return Lexer{ chars: input.chars().collect(), pos: 0, line: 1, column: 1 };
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        return self.chars.get(self.pos + n).cloned();
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        return Some(c);
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token,usize,usize)>,DotError> {
+        let mut ret = vec![];
+
+        while let Some(c) = self.peek(0) {
+            let (line,column) = (self.line,self.column);
+
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+
+            // comments and C preprocessor output
+            if (c == '/' && self.peek(1) == Some('/')) || (c == '#' && column == 1) {
+                while self.peek(0).is_some_and(|c| c != '\n') { self.bump(); }
+                continue;
+            }
+
+            if c == '/' && self.peek(1) == Some('*') {
+                self.bump();
+                self.bump();
+
+                while !(self.peek(0) == Some('*') && self.peek(1) == Some('/')) {
+                    if self.bump().is_none() {
+                        return Err(error(line,column,"unterminated comment".to_string()));
+                    }
+                }
+
+                self.bump();
+                self.bump();
+                continue;
+            }
+
+            let tok = match (c,self.peek(1)) {
+                ('{',_) => Token::LBrace,
+                ('}',_) => Token::RBrace,
+                ('[',_) => Token::LBracket,
+                (']',_) => Token::RBracket,
+                (';',_) => Token::Semicolon,
+                (',',_) => Token::Comma,
+                (':',_) => Token::Colon,
+                ('=',_) => Token::Equal,
+                ('+',_) => Token::Plus,
+                ('-',Some('>')) => { self.bump(); Token::Arrow }
+                ('-',Some('-')) => { self.bump(); Token::Line }
+                ('"',_) => Token::Id(self.quoted(line,column)?,true),
+                ('<',_) => Token::Id(self.html(line,column)?,true),
+                _ if c.is_ascii_digit() || c == '.' || c == '-' => Token::Id(self.numeral(line,column)?,false),
+                _ if c.is_alphabetic() || c == '_' || !c.is_ascii() => Token::Id(self.identifier(),false),
+                _ => return Err(error(line,column,format!("unexpected '{}'",c))),
+            };
+
+            if let Token::Id(..) = tok {} else { self.bump(); }
+            ret.push((tok,line,column));
+        }
+
+        ret.push((Token::End,self.line,self.column));
+        return Ok(ret);
+    }
+
+    fn quoted(&mut self, line: usize, column: usize) -> Result<String,DotError> {
+        let mut ret = String::new();
+
+        self.bump();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(ret),
+                Some('\\') if self.peek(0) == Some('"') => { self.bump(); ret.push('"'); }
+                Some('\\') if self.peek(0) == Some('\\') => { self.bump(); ret.push('\\'); }
+                Some('\\') if self.peek(0) == Some('\n') => { self.bump(); }
+                Some(c) => ret.push(c),
+                None => return Err(error(line,column,"unterminated string".to_string())),
+            }
+        }
+    }
+
+    fn html(&mut self, line: usize, column: usize) -> Result<String,DotError> {
+        let mut ret = String::new();
+        let mut depth = 1;
+
+        self.bump();
+        loop {
+            match self.bump() {
+                Some('<') => depth += 1,
+                Some('>') => {
+                    depth -= 1;
+                    if depth == 0 { return Ok(ret); }
+                }
+                Some(_) => {}
+                None => return Err(error(line,column,"unterminated HTML string".to_string())),
+            }
+            ret.push(self.chars[self.pos - 1]);
+        }
+    }
+
+    fn numeral(&mut self, line: usize, column: usize) -> Result<String,DotError> {
+        let mut ret = String::new();
+
+        if self.peek(0) == Some('-') {
+            ret.push('-');
+            self.bump();
+        }
+
+        while let Some(c) = self.peek(0).filter(|&c| c.is_ascii_digit() || c == '.') {
+            ret.push(c);
+            self.bump();
+        }
+
+        if !ret.chars().any(|c| c.is_ascii_digit()) || ret.matches('.').count() > 1 {
+            return Err(error(line,column,format!("invalid numeral '{}'",ret)));
+        }
+
+        return Ok(ret);
+    }
+
+    fn identifier(&mut self) -> String {
+        let mut ret = String::new();
+
+        while let Some(c) = self.peek(0).filter(|&c| c.is_alphanumeric() || c == '_' || !c.is_ascii()) {
+            ret.push(c);
+            self.bump();
+        }
+
+        return ret;
+    }
+}
+
+/// Attribute defaults of the current (sub)graph.
+#[derive(Clone,Default)]
+struct Scope {
+    node: HashMap<String,String>,
+    edge: HashMap<String,String>,
+}
+
+struct Parser {
+    tokens: Vec<(Token,usize,usize)>,
+    pos: usize,
+    directed: bool,
+    strict: bool,
+    nodes: Vec<(String,HashMap<String,String>)>,
+    node_ids: HashMap<String,usize>,
+    /// Endpoints, attributes and the position of the statement of each edge.
+    edges: Vec<(usize,usize,HashMap<String,String>,(usize,usize))>,
+    /// Index into `edges` by endpoints, only filled for strict graphs.
+    edge_ids: HashMap<(usize,usize),usize>,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        return &self.tokens[self.pos].0;
+    }
+
+    fn bump(&mut self) -> Token {
+        let ret = self.tokens[self.pos].0.clone();
+
+        if ret != Token::End {
+            self.pos += 1;
+        }
+
+        return ret;
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T,DotError> {
+        let (ref tok,line,column) = self.tokens[self.pos];
+        let found = match *tok {
+            Token::Id(ref s,_) => format!("'{}'",s),
+            Token::LBrace => "'{'".to_string(),
+            Token::RBrace => "'}'".to_string(),
+            Token::LBracket => "'['".to_string(),
+            Token::RBracket => "']'".to_string(),
+            Token::Semicolon => "';'".to_string(),
+            Token::Comma => "','".to_string(),
+            Token::Colon => "':'".to_string(),
+            Token::Equal => "'='".to_string(),
+            Token::Plus => "'+'".to_string(),
+            Token::Arrow => "'->'".to_string(),
+            Token::Line => "'--'".to_string(),
+            Token::End => "end of input".to_string(),
+        };
+
+        return Err(error(line,column,format!("expected {}, found {}",message,found)));
+    }
+
+    fn expect(&mut self, tok: Token, what: &str) -> Result<(),DotError> {
+        if *self.peek() == tok {
+            self.bump();
+            return Ok(());
+        } else {
+            return self.error(what);
+        }
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        match *self.peek() {
+            Token::Id(ref s,false) => return s.eq_ignore_ascii_case(kw),
+            _ => return false,
+        }
+    }
+
+    /// An ID, concatenating quoted strings joined by `+`.
+    fn id(&mut self) -> Result<String,DotError> {
+        let (mut s,quoted) = match *self.peek() {
+            Token::Id(ref s,quoted) => (s.clone(),quoted),
+            _ => return self.error("identifier"),
+        };
+
+        self.bump();
+        while quoted && *self.peek() == Token::Plus {
+            self.bump();
+
+            match *self.peek() {
+                Token::Id(ref t,true) => s.push_str(t),
+                _ => return self.error("string after '+'"),
+            }
+            self.bump();
+        }
+
+        return Ok(s);
+    }
+
+    fn graph(&mut self) -> Result<(),DotError> {
+        if self.is_keyword("strict") {
+            self.strict = true;
+            self.bump();
+        }
+
+        if self.is_keyword("digraph") {
+            self.directed = true;
+        } else if self.is_keyword("graph") {
+            self.directed = false;
+        } else {
+            return self.error("'graph' or 'digraph'");
+        }
+        self.bump();
+
+        if let Token::Id(..) = *self.peek() {
+            self.id()?;
+        }
+
+        self.expect(Token::LBrace,"'{'")?;
+        self.statements(&mut Scope::default())?;
+        self.expect(Token::RBrace,"'}'")?;
+
+        return self.expect(Token::End,"end of input");
+    }
+
+    /// Statements up to the closing brace. Returns the nodes mentioned.
+    fn statements(&mut self, scope: &mut Scope) -> Result<Vec<usize>,DotError> {
+        let mut nodes = vec![];
+
+        while *self.peek() != Token::RBrace && *self.peek() != Token::End {
+            self.statement(scope,&mut nodes)?;
+
+            if *self.peek() == Token::Semicolon {
+                self.bump();
+            }
+        }
+
+        return Ok(nodes);
+    }
+
+    fn statement(&mut self, scope: &mut Scope, nodes: &mut Vec<usize>) -> Result<(),DotError> {
+        if self.is_keyword("node") || self.is_keyword("edge") || self.is_keyword("graph") {
+            let is_node = self.is_keyword("node");
+            let is_edge = self.is_keyword("edge");
+
+            self.bump();
+            let attrs = self.attributes()?;
+
+            if is_node {
+                scope.node.extend(attrs);
+            } else if is_edge {
+                scope.edge.extend(attrs);
+            }
+
+            return Ok(());
+        }
+
+        let (_,line,column) = self.tokens[self.pos];
+        let first = match *self.peek() {
+            Token::LBrace => self.subgraph(scope)?,
+            Token::Id(..) if self.is_keyword("subgraph") => self.subgraph(scope)?,
+            Token::Id(..) => {
+                let id = self.id()?;
+
+                // graph attribute
+                if *self.peek() == Token::Equal {
+                    self.bump();
+                    self.id()?;
+                    return Ok(());
+                }
+
+                self.port()?;
+                vec![self.node(id,scope)]
+            }
+            _ => return self.error("statement"),
+        };
+
+        nodes.extend(first.iter().cloned());
+
+        if *self.peek() == Token::Arrow || *self.peek() == Token::Line {
+            let mut operands = vec![first];
+
+            while *self.peek() == Token::Arrow || *self.peek() == Token::Line {
+                if (*self.peek() == Token::Arrow) != self.directed {
+                    return self.error(if self.directed { "'->'" } else { "'--'" });
+                }
+
+                self.bump();
+
+                let next = match *self.peek() {
+                    Token::LBrace => self.subgraph(scope)?,
+                    Token::Id(..) if self.is_keyword("subgraph") => self.subgraph(scope)?,
+                    Token::Id(..) => {
+                        let id = self.id()?;
+
+                        self.port()?;
+                        vec![self.node(id,scope)]
+                    }
+                    _ => return self.error("node or subgraph"),
+                };
+
+                nodes.extend(next.iter().cloned());
+                operands.push(next);
+            }
+
+            let mut attrs = scope.edge.clone();
+
+            attrs.extend(self.attributes()?);
+
+            for pair in operands.windows(2) {
+                for &from in pair[0].iter() {
+                    for &to in pair[1].iter() {
+                        self.edge(from,to,&attrs,(line,column));
+                    }
+                }
+            }
+        } else if first.len() == 1 && *self.peek() == Token::LBracket {
+            let attrs = self.attributes()?;
+
+            self.nodes[first[0]].1.extend(attrs);
+        }
+
+        return Ok(());
+    }
+
+    fn subgraph(&mut self, scope: &Scope) -> Result<Vec<usize>,DotError> {
+        if self.is_keyword("subgraph") {
+            self.bump();
+
+            if let Token::Id(..) = *self.peek() {
+                self.id()?;
+            }
+        }
+
+        self.expect(Token::LBrace,"'{'")?;
+        let ret = self.statements(&mut scope.clone())?;
+        self.expect(Token::RBrace,"'}'")?;
+
+        return Ok(ret);
+    }
+
+    /// Optional `:port[:compass]` after a node ID.
+    fn port(&mut self) -> Result<(),DotError> {
+        for _ in 0..2 {
+            if *self.peek() == Token::Colon {
+                self.bump();
+                self.id()?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Zero or more `[a=b, ...]` lists.
+    fn attributes(&mut self) -> Result<HashMap<String,String>,DotError> {
+        let mut ret = HashMap::new();
+
+        while *self.peek() == Token::LBracket {
+            self.bump();
+
+            while *self.peek() != Token::RBracket {
+                let key = self.id()?;
+
+                self.expect(Token::Equal,"'='")?;
+                ret.insert(key,self.id()?);
+
+                if *self.peek() == Token::Comma || *self.peek() == Token::Semicolon {
+                    self.bump();
+                }
+            }
+
+            self.bump();
+        }
+
+        return Ok(ret);
+    }
+
+    /// Adds an edge. In strict graphs a repeated edge updates the attributes of the first one.
+    fn edge(&mut self, from: usize, to: usize, attrs: &HashMap<String,String>, pos: (usize,usize)) {
+        if self.strict {
+            let key = if self.directed || from <= to { (from,to) } else { (to,from) };
+
+            if let Some(&idx) = self.edge_ids.get(&key) {
+                self.edges[idx].2.extend(attrs.iter().map(|(k,v)| (k.clone(),v.clone())));
+                return;
+            }
+
+            self.edge_ids.insert(key,self.edges.len());
+        }
+
+        self.edges.push((from,to,attrs.clone(),pos));
+    }
+
+    fn node(&mut self, id: String, scope: &Scope) -> usize {
+        if let Some(&idx) = self.node_ids.get(&id) {
+            return idx;
+        }
+
+        self.node_ids.insert(id.clone(),self.nodes.len());
+        self.nodes.push((id,scope.node.clone()));
+        return self.nodes.len() - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adjacency_list::AdjacencyList;
+    use adjacency_matrix::AdjacencyMatrix;
+    use order::weak_topo_order;
+    use traits::{
+        MutableGraph,
+        IncidenceGraph,
+        BidirectionalGraph,
+        DirectedEdges,
+        UndirectedEdges,
+    };
+
+    #[test]
+    fn write_dot() {
+        let mut g = AdjacencyList::<&'static str,usize>::new();
+        let a = g.add_vertex("entry");
+        let b = g.add_vertex("say \"hi\"");
+        let c = g.add_vertex("exit");
+
+        g.add_edge(1,a,b);
+        g.add_edge(2,b,c);
+        g.add_edge(3,a,c);
+
+        let dot = DotWriter::new(&g)
+            .graph_attribute("rankdir","LR")
+            .vertex_attributes(|_,lb| vec![("label".to_string(),lb.to_string()),("shape".to_string(),"box".to_string())])
+            .edge_attributes(|_,&w| if w > 1 { vec![("color".to_string(),"red".to_string())] } else { vec![] })
+            .to_string();
+
+        assert_eq!(dot, "digraph {
+    \"rankdir\"=\"LR\";
+    n0 [\"label\"=\"entry\",\"shape\"=\"box\"];
+    n1 [\"label\"=\"say \\\"hi\\\"\",\"shape\"=\"box\"];
+    n2 [\"label\"=\"exit\",\"shape\"=\"box\"];
+    n0 -> n1;
+    n1 -> n2 [\"color\"=\"red\"];
+    n0 -> n2 [\"color\"=\"red\"];
+}
+");
+
+        let mut buf = vec![];
+        DotWriter::new(&g).undirected().write(&mut buf).unwrap();
+
+        assert_eq!(String::from_utf8(buf).unwrap(), "graph {\n    n0;\n    n1;\n    n2;\n    n0 -- n1;\n    n1 -- n2;\n    n0 -- n2;\n}\n");
+    }
+
+    #[test]
+    fn write_matrix() {
+        let row1 = [None,Some(())];
+        let row2 = [Some(()),None];
+        let rows: [&[Option<()>]; 2] = [&row1,&row2];
+        let labels = [1,2];
+        let mat = AdjacencyMatrix::new(&rows,&labels);
+        let dot = DotWriter::new(&mat).to_string();
+
+        assert_eq!(dot, "digraph {\n    n0;\n    n1;\n    n0 -> n1;\n    n1 -> n0;\n}\n");
+    }
+
+    #[test]
+    fn clusters() {
+        // 1 -> 2 -> 3 -> 4 -> 2, 3 -> 3, 4 -> 5
+        let mut g = AdjacencyList::<usize,()>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+        let v5 = g.add_vertex(5);
+
+        g.add_edge((),v1,v2);
+        g.add_edge((),v2,v3);
+        g.add_edge((),v3,v4);
+        g.add_edge((),v4,v2);
+        g.add_edge((),v3,v3);
+        g.add_edge((),v4,v5);
+
+        let clusters = Cluster::from_ordering(&weak_topo_order(v1,&g));
+
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].vertices, vec![v2,v4]);
+        assert_eq!(clusters[0].clusters, vec![Cluster::new(vec![v3])]);
+
+        let mut outer = clusters[0].clone();
+        outer.attributes.push(("color".to_string(),"blue".to_string()));
+
+        let dot = DotWriter::new(&g).cluster(outer).to_string();
+
+        assert_eq!(dot, "digraph {
+    n0;
+    n4;
+    subgraph cluster_0 {
+        \"color\"=\"blue\";
+        n1;
+        n3;
+        subgraph cluster_1 {
+            n2;
+        }
+    }
+    n0 -> n1;
+    n1 -> n2;
+    n2 -> n3;
+    n3 -> n1;
+    n2 -> n2;
+    n3 -> n4;
+}
+");
+    }
+
+    fn label(id: &str, attrs: &HashMap<String,String>) -> String {
+        return attrs.get("label").cloned().unwrap_or(id.to_string());
+    }
+
+    fn weight(attrs: &HashMap<String,String>) -> usize {
+        return attrs.get("weight").map(|x| x.parse().unwrap()).unwrap_or(0);
+    }
+
+    #[test]
+    fn parse() {
+        let input = r#"
+            /* control flow */
+            strict digraph "main" {
+                rankdir=LR
+                node [shape=box];
+                entry [label="start" + " here"];
+                entry -> b1 -> b2 [weight=2];
+                b1 -> { b3; "b4" } // two edges
+                edge [weight=7]
+                subgraph cluster_loop {
+                    node [shape=ellipse, label=loop]
+                    b3:n -> b3:s
+                }
+                b4 -> exit;
+                exit [label=<<b>end</b>>]
+            }
+        "#;
+        let (g,ids) = parse_dot::<String,usize,DirectedEdges,_,_>(input,label,weight).unwrap();
+
+        assert_eq!(g.num_vertices(), 6);
+        assert_eq!(g.num_edges(), 6);
+        assert_eq!(g.vertex_label(ids["entry"]), Some(&"start here".to_string()));
+        assert_eq!(g.vertex_label(ids["b4"]), Some(&"b4".to_string()));
+        assert_eq!(g.vertex_label(ids["exit"]), Some(&"<b>end</b>".to_string()));
+        assert_eq!(g.out_edges(ids["entry"]).map(|e| (g.target(e),g.edge_label(e))).collect::<Vec<_>>(), vec![(ids["b1"],Some(&2))]);
+        assert_eq!(g.out_edges(ids["b1"]).map(|e| g.target(e)).collect::<Vec<_>>(), vec![ids["b2"],ids["b3"],ids["b4"]]);
+        assert_eq!(g.edges_between(ids["b3"],ids["b3"]).map(|e| g.edge_label(e)).collect::<Vec<_>>(), vec![Some(&7)]);
+        assert_eq!(g.edges_between(ids["b4"],ids["exit"]).map(|e| g.edge_label(e)).collect::<Vec<_>>(), vec![Some(&7)]);
+    }
+
+    #[test]
+    fn parse_strict() {
+        let (g,ids) = parse_dot::<String,usize,DirectedEdges,_,_>("strict digraph { a -> b [weight=1]; b -> a; a -> b [weight=2] }",label,weight).unwrap();
+
+        assert_eq!(g.num_edges(), 2);
+        assert_eq!(g.edges_between(ids["a"],ids["b"]).map(|e| g.edge_label(e)).collect::<Vec<_>>(), vec![Some(&2)]);
+
+        let (g,ids) = parse_dot::<String,usize,UndirectedEdges,_,_>("strict graph { a -- b [weight=3]; b -- a; a -- a; a -- a }",label,weight).unwrap();
+
+        assert_eq!(g.num_edges(), 2);
+        assert_eq!(g.edges_between(ids["a"],ids["b"]).map(|e| g.edge_label(e)).collect::<Vec<_>>(), vec![Some(&3)]);
+
+        let (g,_) = parse_dot::<String,usize,DirectedEdges,_,_>("digraph { a -> b; a -> b }",label,weight).unwrap();
+
+        assert_eq!(g.num_edges(), 2);
+    }
+
+    #[test]
+    fn parse_undirected() {
+        let (g,ids) = parse_dot::<String,usize,UndirectedEdges,_,_>("graph { a -- b -- c; c -- a }",label,weight).unwrap();
+
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.degree(ids["a"]), 2);
+        assert_eq!(g.out_edges(ids["c"]).map(|e| g.opposite(e,ids["c"])).collect::<Vec<_>>(), vec![ids["b"],ids["a"]]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |s: &str| parse_dot::<String,usize,DirectedEdges,_,_>(s,label,weight).err().unwrap();
+
+        assert_eq!((err("digraph {\n  a -- b\n}").line,err("digraph {\n  a -- b\n}").column), (2,5));
+        assert_eq!(err("digraph {\n  a -- b\n}").message, "expected '->', found '--'");
+        assert_eq!((err("digraph { a [label=] }").line,err("digraph { a [label=] }").column), (1,20));
+        assert_eq!((err("digraph {\n\n  \"abc\n").line,err("digraph {\n\n  \"abc\n").column), (3,3));
+        assert_eq!(err("digraph { a }}").message, "expected end of input, found '}'");
+        assert_eq!(err("tree { }").message, "expected 'graph' or 'digraph', found 'tree'");
+        assert_eq!(err("digraph { a -> }").column, 16);
+        assert_eq!(err("digraph { a -> b").message, "expected '}', found end of input");
+        assert_eq!(err("digraph { a ? b }").to_string(), "1:13: unexpected '?'");
+        assert_eq!(err("digraph { a [label=\"x\" + ] }").to_string(), "1:26: expected string after '+', found ']'");
+        assert_eq!(err("digraph { a [label=").message, "expected identifier, found end of input");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut g = AdjacencyList::<String,usize>::new();
+        let a = g.add_vertex("a \"quoted\"".to_string());
+        let b = g.add_vertex("b".to_string());
+
+        g.add_edge(3,a,b);
+        g.add_edge(4,b,b);
+
+        let dot = DotWriter::new(&g)
+            .vertex_attributes(|_,lb| vec![("label".to_string(),lb.clone())])
+            .edge_attributes(|_,w| vec![("weight".to_string(),w.to_string())])
+            .to_string();
+        let (h,ids) = parse_dot::<String,usize,DirectedEdges,_,_>(&dot,label,weight).unwrap();
+
+        assert_eq!(h.vertex_label(ids["n0"]), Some(&"a \"quoted\"".to_string()));
+        assert_eq!(h.edges().map(|e| (h.source(e),h.target(e),*h.edge_label(e).unwrap())).collect::<Vec<_>>(), vec![(ids["n0"],ids["n1"],3),(ids["n1"],ids["n1"],4)]);
+    }
+
+    #[test]
+    fn quote_backslashes() {
+        assert_eq!(quote("left\\l"), "\"left\\l\"");
+        assert_eq!(quote("a\\b"), "\"a\\\\b\"");
+        assert_eq!(quote("a\\"), "\"a\\\\\"");
+        assert_eq!(quote("\\\""), "\"\\\\\\\"\"");
+
+        let mut g = AdjacencyList::<String,usize>::new();
+
+        g.add_vertex("x\\".to_string());
+        g.add_vertex("y\\\"];\n  evil [label=\"".to_string());
+
+        let dot = DotWriter::new(&g)
+            .vertex_attributes(|_,lb| vec![("label".to_string(),lb.clone())])
+            .to_string();
+        let (h,ids) = parse_dot::<String,usize,DirectedEdges,_,_>(&dot,label,weight).unwrap();
+
+        assert_eq!(h.num_vertices(), 2);
+        assert_eq!(h.vertex_label(ids["n0"]), Some(&"x\\".to_string()));
+        assert_eq!(h.vertex_label(ids["n1"]), Some(&"y\\\"];\\n  evil [label=\"".to_string()));
+    }
+}
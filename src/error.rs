@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::fmt;
+
+/// Reasons a graph operation can fail.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub enum GraphError {
+    /// The vertex descriptor does not refer to a vertex of the graph, e.g. because it was removed.
+    UnknownVertex,
+    /// The edge descriptor does not refer to an edge of the graph.
+    UnknownEdge,
+    /// The graph can hold only one edge between the two vertices and it already has one.
+    EdgeExists,
+    /// The graph does not allow edges from a vertex to itself.
+    SelfLoop,
+    /// The graph refused to add the edge for a reason not covered by the other variants.
+    EdgeRejected,
+    /// The position is past the end of the edge list.
+    InvalidPosition,
+    /// The edges are not a permutation of the edge list they should replace.
+    InvalidOrder,
+    /// A key identifying a vertex was used for more than one vertex.
+    DuplicateKey,
+    /// The internal indices of the graph contradict each other.
+    Inconsistent,
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GraphError::UnknownVertex => f.write_str("unknown vertex"),
+            GraphError::UnknownEdge => f.write_str("unknown edge"),
+            GraphError::EdgeExists => f.write_str("edge already exists"),
+            GraphError::SelfLoop => f.write_str("self-loops not allowed"),
+            GraphError::EdgeRejected => f.write_str("edge rejected"),
+            GraphError::InvalidPosition => f.write_str("invalid edge list position"),
+            GraphError::InvalidOrder => f.write_str("not a permutation of the edge list"),
+            GraphError::DuplicateKey => f.write_str("duplicate vertex key"),
+            GraphError::Inconsistent => f.write_str("inconsistent graph"),
+        }
+    }
+}
+
+impl Error for GraphError {}
@@ -0,0 +1,884 @@
+//! GraphML input and output.
+//!
+//! `write_graphml` writes an `AdjacencyList` as GraphML and `read_graphml` reads it back. Labels
+//! are converted to and from typed GraphML attributes (`<key>` declarations and `<data>` values) by
+//! implementing `ToGraphML` and `FromGraphML`.
+//!
+//! Node and edge IDs written by `write_graphml` encode the descriptors: vertex `(i,g)` becomes
+//! node `n{i}.{g}` and edge `(i,g)` becomes edge `e{i}.{g}`. If all IDs of a document follow this
+//! scheme `read_graphml` restores exactly these descriptors, also with holes left by removals.
+
+use std::collections::{HashMap,HashSet};
+use std::error::Error;
+use std::fmt;
+use std::io::{self,Read,Write};
+
+use xml;
+use xml::reader::{EventReader,XmlEvent as ReaderEvent};
+use xml::writer::{EmitterConfig,EventWriter,XmlEvent as WriterEvent};
+
+use adjacency_list::{
+    AdjacencyList,
+    AdjacencyListVertexDescriptor,
+    AdjacencyListEdgeDescriptor,
+    FreeSlots,
+};
+use copy::DescriptorMaps;
+use traits::{
+    Graph,
+    VertexListGraph,
+    EdgeListGraph,
+    MutableGraph,
+    Direction,
+};
+
+const NAMESPACE: &str = "http://graphml.graphdrawing.org/xmlns";
+
+/// Value of a typed GraphML attribute. The variants correspond to the `attr.type`s of GraphML.
+#[derive(Clone,Debug,PartialEq)]
+pub enum AttributeValue {
+    Boolean(bool),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+}
+
+impl AttributeValue {
+    /// `attr.type` of the value.
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            AttributeValue::Boolean(_) => "boolean",
+            AttributeValue::Int(_) => "int",
+            AttributeValue::Long(_) => "long",
+            AttributeValue::Float(_) => "float",
+            AttributeValue::Double(_) => "double",
+            AttributeValue::String(_) => "string",
+        }
+    }
+
+    /// Parses `value` as an attribute of `attr.type` `ty`. Returns `None` if the type is unknown or
+    /// the value malformed.
+    pub fn parse(ty: &str, value: &str) -> Option<AttributeValue> {
+        let trimmed = value.trim();
+
+        match ty {
+            "boolean" => {
+                match trimmed {
+                    "true" | "1" => Some(AttributeValue::Boolean(true)),
+                    "false" | "0" => Some(AttributeValue::Boolean(false)),
+                    _ => None,
+                }
+            }
+            "int" => trimmed.parse().ok().map(AttributeValue::Int),
+            "long" => trimmed.parse().ok().map(AttributeValue::Long),
+            "float" => trimmed.parse().ok().map(AttributeValue::Float),
+            "double" => trimmed.parse().ok().map(AttributeValue::Double),
+            "string" => Some(AttributeValue::String(value.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AttributeValue::Boolean(x) => write!(f,"{}",x),
+            AttributeValue::Int(x) => write!(f,"{}",x),
+            AttributeValue::Long(x) => write!(f,"{}",x),
+            AttributeValue::Float(x) => write!(f,"{}",x),
+            AttributeValue::Double(x) => write!(f,"{}",x),
+            AttributeValue::String(ref x) => f.write_str(x),
+        }
+    }
+}
+
+/// Conversion of a vertex or edge label into GraphML attributes.
+pub trait ToGraphML {
+    /// Names and values of the attributes of the label. All labels of a graph must use the same
+    /// type for the same name.
+    fn to_graphml(&self) -> Vec<(String,AttributeValue)>;
+}
+
+/// Conversion of GraphML attributes into a vertex or edge label.
+pub trait FromGraphML: Sized {
+    /// Label for the attributes of a node or edge, including the defaults of their keys. Returns
+    /// `None` if the attributes don't describe a label.
+    fn from_graphml(attributes: &HashMap<String,AttributeValue>) -> Option<Self>;
+}
+
+impl ToGraphML for () {
+    fn to_graphml(&self) -> Vec<(String,AttributeValue)> {
+        return vec![];
+    }
+}
+
+impl FromGraphML for () {
+    fn from_graphml(_: &HashMap<String,AttributeValue>) -> Option<Self> {
+        return Some(());
+    }
+}
+
+// Scalars are stored in a single attribute called "label".
+macro_rules! graphml_scalar {
+    ($ty:ty,$variant:ident) => {
+        impl ToGraphML for $ty {
+            fn to_graphml(&self) -> Vec<(String,AttributeValue)> {
+                return vec![("label".to_string(),AttributeValue::$variant(self.clone()))];
+            }
+        }
+
+        impl FromGraphML for $ty {
+            fn from_graphml(attributes: &HashMap<String,AttributeValue>) -> Option<Self> {
+                match attributes.get("label") {
+                    Some(&AttributeValue::$variant(ref x)) => Some(x.clone()),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+graphml_scalar!(bool,Boolean);
+graphml_scalar!(i32,Int);
+graphml_scalar!(i64,Long);
+graphml_scalar!(f32,Float);
+graphml_scalar!(f64,Double);
+graphml_scalar!(String,String);
+
+/// Reasons reading or writing GraphML can fail.
+#[derive(Debug)]
+pub enum GraphMLError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The input is not well-formed XML.
+    Xml(String),
+    /// The document is no GraphML or uses features that can't be represented by an
+    /// `AdjacencyList`, or labels can't be converted.
+    Invalid(String),
+}
+
+impl fmt::Display for GraphMLError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GraphMLError::Io(ref e) => write!(f,"{}",e),
+            GraphMLError::Xml(ref msg) => write!(f,"malformed XML: {}",msg),
+            GraphMLError::Invalid(ref msg) => write!(f,"invalid GraphML: {}",msg),
+        }
+    }
+}
+
+impl Error for GraphMLError {}
+
+impl From<io::Error> for GraphMLError {
+    fn from(e: io::Error) -> Self {
+        return GraphMLError::Io(e);
+    }
+}
+
+impl From<xml::reader::Error> for GraphMLError {
+    fn from(e: xml::reader::Error) -> Self {
+        return GraphMLError::Xml(e.to_string());
+    }
+}
+
+impl From<xml::writer::Error> for GraphMLError {
+    fn from(e: xml::writer::Error) -> Self {
+        match e {
+            xml::writer::Error::Io(e) => GraphMLError::Io(e),
+            e => GraphMLError::Xml(e.to_string()),
+        }
+    }
+}
+
+fn invalid<T>(msg: String) -> Result<T,GraphMLError> {
+    return Err(GraphMLError::Invalid(msg));
+}
+
+/// Attribute names of one domain (`node` or `edge`) with their types and key IDs.
+struct KeyTable {
+    domain: &'static str,
+    keys: Vec<(String,&'static str,String)>,
+    index: HashMap<String,usize>,
+}
+
+impl KeyTable {
+    fn new(domain: &'static str) -> Self {
+        return KeyTable{ domain: domain, keys: vec![], index: HashMap::new() };
+    }
+
+    /// Declares the attributes of a label, returns the key IDs of the values.
+    fn declare(&mut self, attributes: &[(String,AttributeValue)], next_id: &mut usize) -> Result<Vec<String>,GraphMLError> {
+        let mut ret = Vec::with_capacity(attributes.len());
+
+        for (name,value) in attributes.iter() {
+            let idx = match self.index.get(name) {
+                Some(&idx) => idx,
+                None => {
+                    self.keys.push((name.clone(),value.type_name(),format!("d{}",*next_id)));
+                    self.index.insert(name.clone(),self.keys.len() - 1);
+                    *next_id += 1;
+                    self.keys.len() - 1
+                }
+            };
+            let (_,ty,ref id) = self.keys[idx];
+
+            if ty != value.type_name() {
+                return invalid(format!("{} attribute {} is used as {} and {}",self.domain,name,ty,value.type_name()));
+            }
+
+            ret.push(id.clone());
+        }
+
+        return Ok(ret);
+    }
+}
+
+fn start_element<W: Write>(w: &mut EventWriter<W>, name: &str, attributes: &[(&str,&str)]) -> Result<(),GraphMLError> {
+    let mut ev = WriterEvent::start_element(name);
+
+    for &(k,v) in attributes.iter() {
+        ev = ev.attr(k,v);
+    }
+
+    w.write(ev)?;
+    return Ok(());
+}
+
+fn write_data<W: Write>(w: &mut EventWriter<W>, keys: &[String], attributes: &[(String,AttributeValue)]) -> Result<(),GraphMLError> {
+    for (key,(_,value)) in keys.iter().zip(attributes.iter()) {
+        start_element(w,"data",&[("key",key)])?;
+        w.write(WriterEvent::characters(&value.to_string()))?;
+        w.write(WriterEvent::end_element())?;
+    }
+
+    return Ok(());
+}
+
+/// Writes `graph` as GraphML document to `out`.
+///
+/// Every attribute name used by the labels is declared as a `<key>` with the type of its values.
+/// `edgedefault` is `directed` or `undirected` depending on `D`. Nodes and edges are written in
+/// ascending descriptor order with IDs derived from their descriptors. Fails if labels use the
+/// same attribute name with different types.
+pub fn write_graphml<V,E,D,W>(graph: &AdjacencyList<V,E,D>, out: W) -> Result<(),GraphMLError>
+    where V: ToGraphML, E: ToGraphML, D: Direction, W: Write {
+    let mut next_id = 0;
+    let mut node_keys = KeyTable::new("node");
+    let mut edge_keys = KeyTable::new("edge");
+    let mut nodes = Vec::with_capacity(graph.num_vertices());
+    let mut edges = Vec::with_capacity(graph.num_edges());
+
+    for v in graph.vertices() {
+        let attrs = graph.vertex_label(v).unwrap().to_graphml();
+        let keys = node_keys.declare(&attrs,&mut next_id)?;
+
+        nodes.push((node_id(v),attrs,keys));
+    }
+
+    for e in graph.edges() {
+        let attrs = graph.edge_label(e).unwrap().to_graphml();
+        let keys = edge_keys.declare(&attrs,&mut next_id)?;
+
+        edges.push((edge_id(e),node_id(graph.source(e)),node_id(graph.target(e)),attrs,keys));
+    }
+
+    let mut w = EventWriter::new_with_config(out,EmitterConfig::new().perform_indent(true));
+
+    w.write(WriterEvent::start_element("graphml").default_ns(NAMESPACE))?;
+
+    for table in [&node_keys,&edge_keys].iter() {
+        for &(ref name,ty,ref id) in table.keys.iter() {
+            start_element(&mut w,"key",&[("id",id),("for",table.domain),("attr.name",name),("attr.type",ty)])?;
+            w.write(WriterEvent::end_element())?;
+        }
+    }
+
+    let edgedefault = if D::DIRECTED { "directed" } else { "undirected" };
+
+    start_element(&mut w,"graph",&[("id","G"),("edgedefault",edgedefault)])?;
+
+    for (id,attrs,keys) in nodes.iter() {
+        start_element(&mut w,"node",&[("id",id)])?;
+        write_data(&mut w,keys,attrs)?;
+        w.write(WriterEvent::end_element())?;
+    }
+
+    for (id,from,to,attrs,keys) in edges.iter() {
+        start_element(&mut w,"edge",&[("id",id),("source",from),("target",to)])?;
+        write_data(&mut w,keys,attrs)?;
+        w.write(WriterEvent::end_element())?;
+    }
+
+    w.write(WriterEvent::end_element())?;
+    w.write(WriterEvent::end_element())?;
+    w.into_inner().flush()?;
+
+    return Ok(());
+}
+
+fn node_id(v: AdjacencyListVertexDescriptor) -> String {
+    return format!("n{}.{}",v.0,v.1);
+}
+
+fn edge_id(e: AdjacencyListEdgeDescriptor) -> String {
+    return format!("e{}.{}",e.0,e.1);
+}
+
+/// Inverse of `node_id` and `edge_id`.
+fn parse_descriptor(prefix: char, id: &str) -> Option<(usize,u32)> {
+    if !id.starts_with(prefix) {
+        return None;
+    }
+
+    let (idx,generation) = id[1..].split_once('.')?;
+
+    // reject non-canonical numbers so that different IDs never map to the same descriptor
+    if idx.is_empty() || generation.is_empty() || (idx.len() > 1 && idx.starts_with('0')) || (generation.len() > 1 && generation.starts_with('0')) {
+        return None;
+    }
+
+    match (idx.parse(),generation.parse()) {
+        (Ok(idx),Ok(generation)) => Some((idx,generation)),
+        _ => None,
+    }
+}
+
+struct Key {
+    domain: String,
+    name: Option<String>,
+    ty: String,
+    default: Option<AttributeValue>,
+}
+
+enum Element {
+    Node(String,HashMap<String,AttributeValue>),
+    Edge(Option<String>,String,String,HashMap<String,AttributeValue>),
+}
+
+/// Reads a GraphML document into an `AdjacencyList`.
+///
+/// Attributes of nodes and edges are looked up by the `attr.name` of their keys and passed to
+/// `FromGraphML`, keys without a name (e.g. yEd's graphics) are ignored. The direction of every
+/// edge, given by its `directed` attribute or the `edgedefault` of the graph, must match `D`. Read
+/// documents with undirected edges into an `AdjacencyList<V,E,UndirectedEdges>`. Nested graphs,
+/// hyperedges and documents with more than one graph are rejected. Ports are ignored.
+///
+/// If all node and edge IDs follow the scheme of `write_graphml` and their indices are below a
+/// few times the number of nodes resp. edges, the vertices and edges get the descriptors encoded
+/// in their IDs. Unused indices become free slots whose descriptors are newer than any in the
+/// document. Otherwise the vertices and edges are added in document order. Edge lists are in
+/// document order. Returns the graph and maps from node resp. edge IDs to descriptors, edges
+/// without an ID are not in the map.
+pub fn read_graphml<V,E,D,R>(input: R) -> Result<(AdjacencyList<V,E,D>,DescriptorMaps<String,AdjacencyListVertexDescriptor,String,AdjacencyListEdgeDescriptor>),GraphMLError>
+    where V: FromGraphML, E: FromGraphML, D: Direction, R: Read {
+    let mut keys = HashMap::<String,Key>::new();
+    let mut key: Option<(String,Key)> = None;
+    let mut text: Option<String> = None;
+    let mut data_key: Option<String> = None;
+    let mut element: Option<Element> = None;
+    let mut graphs = 0;
+    let mut in_graph = false;
+    let mut seen_root = false;
+    let mut edgedefault = true;
+    let mut nodes = Vec::<(String,V)>::new();
+    let mut edges = Vec::<(Option<String>,String,String,E)>::new();
+
+    for ev in EventReader::new(input) {
+        match ev? {
+            ReaderEvent::StartElement{ name, attributes, .. } => {
+                if name.namespace.as_ref().map(|ns| ns != NAMESPACE).unwrap_or(false) {
+                    continue;
+                }
+
+                let attr = |n: &str| attributes.iter().find(|a| a.name.local_name == n && a.name.prefix.is_none()).map(|a| a.value.clone());
+
+                match &name.local_name[..] {
+                    "graphml" => {
+                        seen_root = true;
+                    }
+                    "key" => {
+                        let id = match attr("id") {
+                            Some(id) => id,
+                            None => return invalid("key without id".to_string()),
+                        };
+
+                        key = Some((id,Key{
+                            domain: attr("for").unwrap_or_else(|| "all".to_string()),
+                            name: attr("attr.name"),
+                            ty: attr("attr.type").unwrap_or_else(|| "string".to_string()),
+                            default: None,
+                        }));
+                    }
+                    "default" if key.is_some() => {
+                        text = Some(String::new());
+                    }
+                    "graph" => {
+                        graphs += 1;
+
+                        if graphs > 1 {
+                            return invalid("nested or multiple graphs are not supported".to_string());
+                        }
+
+                        in_graph = true;
+                        edgedefault = match attr("edgedefault").as_ref().map(|s| &s[..]) {
+                            Some("directed") | None => true,
+                            Some("undirected") => false,
+                            Some(s) => return invalid(format!("unknown edgedefault {}",s)),
+                        };
+                    }
+                    "node" if in_graph => {
+                        let id = match attr("id") {
+                            Some(id) => id,
+                            None => return invalid("node without id".to_string()),
+                        };
+
+                        element = Some(Element::Node(id,defaults(&keys,"node")));
+                    }
+                    "edge" if in_graph => {
+                        let (from,to) = match (attr("source"),attr("target")) {
+                            (Some(from),Some(to)) => (from,to),
+                            _ => return invalid("edge without source or target".to_string()),
+                        };
+                        let directed = match attr("directed").as_ref().map(|s| &s[..]) {
+                            Some("true") => true,
+                            Some("false") => false,
+                            None => edgedefault,
+                            Some(s) => return invalid(format!("invalid directed attribute {}",s)),
+                        };
+
+                        if directed != D::DIRECTED {
+                            let what = if directed { "directed" } else { "undirected" };
+                            return invalid(format!("{} edge in a graph of the other kind",what));
+                        }
+
+                        element = Some(Element::Edge(attr("id"),from,to,defaults(&keys,"edge")));
+                    }
+                    "hyperedge" => {
+                        return invalid("hyperedges are not supported".to_string());
+                    }
+                    "data" if element.is_some() => {
+                        match attr("key") {
+                            Some(k) => {
+                                data_key = Some(k);
+                                text = Some(String::new());
+                            }
+                            None => return invalid("data without key".to_string()),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            ReaderEvent::Characters(s) | ReaderEvent::CData(s) | ReaderEvent::Whitespace(s) => {
+                if let Some(ref mut text) = text {
+                    text.push_str(&s);
+                }
+            }
+            ReaderEvent::EndElement{ name } => {
+                if name.namespace.as_ref().map(|ns| ns != NAMESPACE).unwrap_or(false) {
+                    continue;
+                }
+
+                match &name.local_name[..] {
+                    "key" => {
+                        if let Some((id,k)) = key.take() {
+                            keys.insert(id,k);
+                        }
+                    }
+                    "default" if key.is_some() => {
+                        let value = text.take().unwrap_or_default();
+                        let (ref id,ref mut k) = *key.as_mut().unwrap();
+
+                        k.default = Some(parse_value(id,&k.ty,&value)?);
+                    }
+                    "graph" => {
+                        in_graph = false;
+                    }
+                    "data" if data_key.is_some() => {
+                        let id = data_key.take().unwrap();
+                        let value = text.take().unwrap_or_default();
+                        let k = match keys.get(&id) {
+                            Some(k) => k,
+                            None => return invalid(format!("undeclared key {}",id)),
+                        };
+
+                        if let Some(ref name) = k.name {
+                            let value = parse_value(&id,&k.ty,&value)?;
+
+                            match element {
+                                Some(Element::Node(_,ref mut attrs)) | Some(Element::Edge(_,_,_,ref mut attrs)) => {
+                                    attrs.insert(name.clone(),value);
+                                }
+                                None => {}
+                            }
+                        }
+                    }
+                    "node" | "edge" => {
+                        match element.take() {
+                            Some(Element::Node(id,attrs)) => {
+                                match V::from_graphml(&attrs) {
+                                    Some(lb) => nodes.push((id,lb)),
+                                    None => return invalid(format!("node {} has no valid label",id)),
+                                }
+                            }
+                            Some(Element::Edge(id,from,to,attrs)) => {
+                                match E::from_graphml(&attrs) {
+                                    Some(lb) => edges.push((id,from,to,lb)),
+                                    None => return invalid(format!("edge {} -> {} has no valid label",from,to)),
+                                }
+                            }
+                            None => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !seen_root {
+        return invalid("no graphml element".to_string());
+    }
+
+    return build_graph(nodes,edges);
+}
+
+/// Default values of the keys for `domain`, by attribute name.
+fn defaults(keys: &HashMap<String,Key>, domain: &str) -> HashMap<String,AttributeValue> {
+    let mut ret = HashMap::new();
+
+    for k in keys.values() {
+        if let (Some(name),Some(value)) = (&k.name,&k.default) {
+            if k.domain == domain || k.domain == "all" {
+                ret.insert(name.clone(),value.clone());
+            }
+        }
+    }
+
+    return ret;
+}
+
+fn parse_value(key: &str, ty: &str, value: &str) -> Result<AttributeValue,GraphMLError> {
+    match AttributeValue::parse(ty,value) {
+        Some(value) => return Ok(value),
+        None => return invalid(format!("invalid {} value {:?} for key {}",ty,value,key)),
+    }
+}
+
+fn build_graph<V,E,D: Direction>(nodes: Vec<(String,V)>, edges: Vec<(Option<String>,String,String,E)>) -> Result<(AdjacencyList<V,E,D>,DescriptorMaps<String,AdjacencyListVertexDescriptor,String,AdjacencyListEdgeDescriptor>),GraphMLError> {
+    let mut node_ids = HashSet::with_capacity(nodes.len());
+    let mut edge_ids = HashSet::with_capacity(edges.len());
+
+    for (id,_) in nodes.iter() {
+        if !node_ids.insert(id.clone()) {
+            return invalid(format!("duplicate node {}",id));
+        }
+    }
+
+    for (id,from,to,_) in edges.iter() {
+        if let Some(ref id) = *id {
+            if !edge_ids.insert(id.clone()) {
+                return invalid(format!("duplicate edge {}",id));
+            }
+        }
+
+        for v in [from,to].iter() {
+            if !node_ids.contains(*v) {
+                return invalid(format!("edge refers to unknown node {}",v));
+            }
+        }
+    }
+
+    // IDs written by `write_graphml`
+    let vertex_descs = nodes.iter().map(|(id,_)| parse_descriptor('n',id)).collect::<Option<Vec<_>>>();
+    let edge_descs = edges.iter().map(|(id,_,_,_)| id.as_ref().and_then(|id| parse_descriptor('e',id))).collect::<Option<Vec<_>>>();
+    let vertex_holes = vertex_descs.as_ref().and_then(|descs| holes(descs));
+    let edge_holes = edge_descs.as_ref().and_then(|descs| holes(descs));
+
+    if let (Some(vertex_descs),Some(edge_descs),Some(vertex_holes),Some(edge_holes)) = (vertex_descs,edge_descs,vertex_holes,edge_holes) {
+        let vertex_map = nodes.iter().zip(vertex_descs.iter())
+            .map(|((id,_),&(idx,generation))| (id.clone(),AdjacencyListVertexDescriptor(idx,generation)))
+            .collect::<HashMap<_,_>>();
+        let edge_map = edges.iter().zip(edge_descs.iter())
+            .map(|((id,_,_,_),&(idx,generation))| (id.clone().unwrap(),AdjacencyListEdgeDescriptor(idx,generation)))
+            .collect::<HashMap<_,_>>();
+        let vertices = nodes.into_iter().map(|(id,lb)| (vertex_map[&id],lb)).collect();
+        let edges = edges.into_iter().map(|(id,from,to,lb)| (edge_map[id.as_ref().unwrap()],vertex_map[&from],vertex_map[&to],lb)).collect();
+
+        let free = FreeSlots{
+            vertices: vertex_holes.0,
+            edges: edge_holes.0,
+            vertex_generation: vertex_holes.1,
+            edge_generation: edge_holes.1,
+        };
+
+        match AdjacencyList::with_descriptors(vertices,edges,free,false) {
+            Ok(g) => return Ok((g,(vertex_map,edge_map))),
+            Err(e) => return invalid(format!("node or edge IDs name the same descriptor: {}",e)),
+        }
+    }
+
+    let mut ret = AdjacencyList::<V,E,D>::empty(false);
+    let mut vertex_map = HashMap::with_capacity(nodes.len());
+    let mut edge_map = HashMap::with_capacity(edges.len());
+
+    ret.reserve(nodes.len(),edges.len());
+
+    for (id,lb) in nodes {
+        vertex_map.insert(id,ret.add_vertex(lb));
+    }
+
+    for (id,from,to,lb) in edges {
+        let e = ret.add_edge(lb,vertex_map[&from],vertex_map[&to]).unwrap();
+
+        if let Some(id) = id {
+            edge_map.insert(id,e);
+        }
+    }
+
+    return Ok((ret,(vertex_map,edge_map)));
+}
+
+/// Free slots between the `(index, generation)` descriptors and the generation of new slots, both
+/// newer than any descriptor. `None` if the holes would outnumber the descriptors more than a few
+/// times, so the slabs don't allocate for slots the document doesn't pay for.
+fn holes(descs: &[(usize,u32)]) -> Option<(Vec<(usize,u32)>,u32)> {
+    let bound = descs.len().saturating_mul(4).saturating_add(16);
+    let len = match descs.iter().map(|&(idx,_)| idx.saturating_add(1)).max() {
+        Some(len) if len > bound => return None,
+        Some(len) => len,
+        None => 0,
+    };
+    let generation = descs.iter().map(|&(_,generation)| generation.saturating_add(1)).max().unwrap_or(0);
+    let mut used = vec![false; len];
+
+    for &(idx,_) in descs.iter() {
+        used[idx] = true;
+    }
+
+    return Some(((0..len).filter(|&idx| !used[idx]).map(|idx| (idx,generation)).collect(),generation));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use traits::{IncidenceGraph,BidirectionalGraph};
+    use traits::{UndirectedEdges,DirectedEdges};
+
+    #[derive(Clone,Debug,PartialEq)]
+    struct Block {
+        name: String,
+        size: i64,
+        entry: bool,
+    }
+
+    impl ToGraphML for Block {
+        fn to_graphml(&self) -> Vec<(String,AttributeValue)> {
+            return vec![
+                ("name".to_string(),AttributeValue::String(self.name.clone())),
+                ("size".to_string(),AttributeValue::Long(self.size)),
+                ("entry".to_string(),AttributeValue::Boolean(self.entry)),
+            ];
+        }
+    }
+
+    impl FromGraphML for Block {
+        fn from_graphml(attrs: &HashMap<String,AttributeValue>) -> Option<Self> {
+            match (attrs.get("name"),attrs.get("size"),attrs.get("entry")) {
+                (Some(AttributeValue::String(name)),Some(&AttributeValue::Long(size)),Some(&AttributeValue::Boolean(entry))) => {
+                    Some(Block{ name: name.clone(), size: size, entry: entry })
+                }
+                _ => None,
+            }
+        }
+    }
+
+    fn block(name: &str, size: i64) -> Block {
+        return Block{ name: name.to_string(), size: size, entry: false };
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut g = AdjacencyList::<Block,f64>::new();
+        let v1 = g.add_vertex(Block{ name: "entry <&>".to_string(), size: 4, entry: true });
+        let v2 = g.add_vertex(block("b2",8));
+        let v3 = g.add_vertex(block("b3",12));
+        let v4 = g.add_vertex(block("b4",16));
+
+        let e12 = g.add_edge(0.5,v1,v2).unwrap();
+        let e12b = g.add_edge(1.5,v1,v2).unwrap();
+        let e23 = g.add_edge(-2.0,v2,v3).unwrap();
+        let e34 = g.add_edge(3.0,v3,v4).unwrap();
+        let e33 = g.add_edge(0.0,v3,v3).unwrap();
+
+        g.remove_edge(e12b);
+        g.remove_vertex(v4);
+
+        let v5 = g.add_vertex(block("b5",20));
+        let e51 = g.add_edge(0.25,v5,v1).unwrap();
+        let e52 = g.add_edge(0.75,v5,v2).unwrap();
+
+        assert!(v5.0 == v4.0 && v5 != v4);
+        assert!(e51.0 + e52.0 == e12b.0 + e34.0 && e51 != e12b && e51 != e34);
+
+        let mut buf = Vec::new();
+
+        write_graphml(&g,&mut buf).unwrap();
+
+        let xml = String::from_utf8(buf.clone()).unwrap();
+
+        assert!(xml.contains("edgedefault=\"directed\""));
+        assert!(xml.contains("attr.name=\"size\" attr.type=\"long\""));
+
+        let (h,(vm,em)) = read_graphml::<Block,f64,DirectedEdges,_>(&buf[..]).unwrap();
+
+        assert_eq!(h.check_invariants(), Ok(()));
+        assert_eq!(h.vertices().collect::<Vec<_>>(), g.vertices().collect::<Vec<_>>());
+        assert_eq!(h.edges().collect::<Vec<_>>(), g.edges().collect::<Vec<_>>());
+        assert_eq!(vm["n3.1"], v5);
+        assert_eq!(em.len(), 5);
+
+        for v in g.vertices() {
+            assert_eq!(h.vertex_label(v), g.vertex_label(v));
+        }
+
+        for e in g.edges() {
+            assert_eq!(h.edge_label(e), g.edge_label(e));
+            assert_eq!(h.source(e), g.source(e));
+            assert_eq!(h.target(e), g.target(e));
+        }
+
+        assert!(h.edge_label(e12b).is_none() && h.edge_label(e34).is_none() && h.vertex_label(v4).is_none());
+        assert_eq!(h.out_edges(v3).collect::<Vec<_>>(), vec![e33]);
+        assert_eq!(h.out_degree(v5), 2);
+        assert!(h.out_edges(v5).any(|e| e == e51) && h.out_edges(v5).any(|e| e == e52));
+        assert_eq!(h.in_edges(v2).collect::<Vec<_>>(), vec![e12,e52]);
+        assert_eq!(h.edge_label(e23), Some(&-2.0));
+
+        // holes in the index range become free slots
+        g.remove_vertex(v1);
+        g.remove_vertex(v2);
+
+        let mut buf = Vec::new();
+
+        write_graphml(&g,&mut buf).unwrap();
+
+        let (mut h,(vm,em)) = read_graphml::<Block,f64,DirectedEdges,_>(&buf[..]).unwrap();
+
+        assert_eq!(h.check_invariants(), Ok(()));
+        assert_eq!(h.num_vertices(), 2);
+        assert_eq!(h.num_edges(), 1);
+        assert_eq!(vm["n2.0"], v3);
+        assert_eq!(vm["n3.1"], v5);
+        assert_eq!(em[&format!("e{}.0",e33.0)], e33);
+        assert_eq!(h.vertex_label(v3), g.vertex_label(v3));
+        assert_eq!(h.source(e33), v3);
+        assert!(h.vertex_label(v1).is_none() && h.vertex_label(v2).is_none() && h.edge_label(e12).is_none());
+
+        let v6 = h.add_vertex(Block{ name: "f".to_string(), size: 0, entry: false });
+
+        assert!(v6 != v1 && v6 != v2 && v6.0 < 4);
+        assert_eq!(h.check_invariants(), Ok(()));
+
+        // indices far beyond the number of nodes are not trusted
+        let input = r#"<graphml><graph edgedefault="directed"><node id="n1000000.0"/></graph></graphml>"#;
+        let (h,(vm,_)) = read_graphml::<(),(),DirectedEdges,_>(input.as_bytes()).unwrap();
+
+        assert_eq!(vm["n1000000.0"].0, 0);
+        assert_eq!(h.num_vertices(), 1);
+    }
+
+    #[test]
+    fn undirected_parallel() {
+        let mut g = AdjacencyList::<i32,(),UndirectedEdges>::undirected();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+
+        g.add_edge((),v1,v2);
+        g.add_edge((),v2,v1);
+
+        let mut buf = Vec::new();
+
+        write_graphml(&g,&mut buf).unwrap();
+
+        let (h,_) = read_graphml::<i32,(),UndirectedEdges,_>(&buf[..]).unwrap();
+
+        assert_eq!(h.num_edges(), 2);
+        assert_eq!(h.edges_between(v1,v2).count(), 2);
+        assert_eq!(h.out_degree(v2), 2);
+        assert_eq!(h.check_invariants(), Ok(()));
+
+        // direction must match
+        assert!(read_graphml::<i32,(),DirectedEdges,_>(&buf[..]).is_err());
+    }
+
+    #[test]
+    fn foreign_document() {
+        let doc = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:y="http://www.yworks.com/xml/graphml">
+  <key id="k0" for="node" attr.name="label" attr.type="string"><default>unnamed</default></key>
+  <key id="k1" for="edge" attr.name="label" attr.type="double"><default>1.0</default></key>
+  <key id="k2" for="node" yfiles.type="nodegraphics"/>
+  <graph id="G" edgedefault="directed">
+    <node id="a"><data key="k0">A</data><data key="k2"><y:ShapeNode><y:NodeLabel>x</y:NodeLabel></y:ShapeNode></data></node>
+    <node id="b"/>
+    <edge source="a" target="b"><data key="k1">2.5</data></edge>
+    <edge id="back" source="b" target="a"/>
+    <edge id="loop" source="a" target="a" directed="true"/>
+  </graph>
+</graphml>"#;
+
+        let (g,(vm,em)) = read_graphml::<String,f64,DirectedEdges,_>(doc.as_bytes()).unwrap();
+        let a = vm["a"];
+        let b = vm["b"];
+
+        assert_eq!(g.num_vertices(), 2);
+        assert_eq!(g.vertex_label(a), Some(&"A".to_string()));
+        assert_eq!(g.vertex_label(b), Some(&"unnamed".to_string()));
+        assert_eq!(g.out_edges(a).map(|e| *g.edge_label(e).unwrap()).collect::<Vec<_>>(), vec![2.5,1.0]);
+        assert_eq!(g.source(em["back"]), b);
+        assert_eq!(em.len(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let wrap = |body: &str| format!("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">{}</graphml>",body);
+        let read = |doc: String| read_graphml::<(),(),DirectedEdges,_>(doc.as_bytes()).err().map(|e| e.to_string());
+
+        assert_eq!(read(wrap("<graph edgedefault=\"directed\"><node id=\"a\"/><edge source=\"a\" target=\"b\"/></graph>")), Some("invalid GraphML: edge refers to unknown node b".to_string()));
+        assert_eq!(read(wrap("<graph edgedefault=\"directed\"><node id=\"a\"/><node id=\"a\"/></graph>")), Some("invalid GraphML: duplicate node a".to_string()));
+        assert_eq!(read(wrap("<graph edgedefault=\"undirected\"><node id=\"a\"/><edge source=\"a\" target=\"a\"/></graph>")), Some("invalid GraphML: undirected edge in a graph of the other kind".to_string()));
+        assert_eq!(read(wrap("<graph edgedefault=\"directed\"><node id=\"a\"><graph edgedefault=\"directed\"/></node></graph>")), Some("invalid GraphML: nested or multiple graphs are not supported".to_string()));
+        assert_eq!(read(wrap("<graph edgedefault=\"directed\"><node id=\"a\"><data key=\"x\">1</data></node></graph>")), Some("invalid GraphML: undeclared key x".to_string()));
+        assert_eq!(read(wrap("<graph edgedefault=\"directed\"><node id=\"n0.0\"/><node id=\"n00.0\"/></graph>")), None);
+        assert_eq!(read(wrap("<graph edgedefault=\"directed\"><node id=\"n1099511627776.0\"/></graph>")), None);
+        assert_eq!(read(wrap("<graph edgedefault=\"directed\"><node id=\"n0.0\"/><node id=\"n0.1\"/></graph>")), Some("invalid GraphML: node or edge IDs name the same descriptor: inconsistent graph".to_string()));
+        assert!(read("<graphml><graph>".to_string()).unwrap().starts_with("malformed XML"));
+
+        let doc = wrap("<key id=\"d0\" for=\"node\" attr.name=\"label\" attr.type=\"int\"/><graph edgedefault=\"directed\"><node id=\"a\"><data key=\"d0\">x</data></node></graph>");
+
+        assert!(read_graphml::<i32,(),DirectedEdges,_>(doc.as_bytes()).is_err());
+
+        // inconsistent attribute types
+        struct Mixed(bool);
+
+        impl ToGraphML for Mixed {
+            fn to_graphml(&self) -> Vec<(String,AttributeValue)> {
+                if self.0 {
+                    return vec![("x".to_string(),AttributeValue::Int(1))];
+                } else {
+                    return vec![("x".to_string(),AttributeValue::Double(1.0))];
+                }
+            }
+        }
+
+        let mut g = AdjacencyList::<Mixed,()>::new();
+
+        g.add_vertex(Mixed(true));
+        g.add_vertex(Mixed(false));
+
+        assert!(write_graphml(&g,Vec::new()).is_err());
+    }
+}
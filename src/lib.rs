@@ -1,18 +1,37 @@
 mod traits;
+mod error;
+mod slab;
 pub mod search;
 pub mod dominator;
 pub mod order;
 pub mod adjacency_list;
 pub mod adjacency_matrix;
+pub mod csr;
+pub mod property_map;
+pub mod view;
+pub mod copy;
+pub mod dot;
+pub mod graphml;
+pub mod text;
+pub mod binary;
 
 #[macro_use]
 extern crate serde_derive;
 extern crate serde;
 extern crate serde_json;
 extern crate bit_set;
+extern crate xml;
+extern crate memmap2;
+extern crate crc32fast;
+extern crate byteorder;
 
+pub use error::GraphError;
 pub use adjacency_list::AdjacencyList;
 pub use adjacency_matrix::AdjacencyMatrix;
+pub use adjacency_matrix::OwnedAdjacencyMatrix;
+pub use csr::CompressedSparseRow;
+pub use copy::{copy_graph,copy_graph_with};
+pub use view::{Reversed,Filtered,InducedSubgraph,Undirected};
 
 pub use traits::Graph as GraphTrait;
 pub use traits::AdjacencyGraph as AdjacencyGraphTrait;
@@ -22,3 +41,7 @@ pub use traits::VertexListGraph as VertexListGraphTrait;
 pub use traits::EdgeListGraph as EdgeListGraphTrait;
 pub use traits::MutableGraph as MutableGraphTrait;
 pub use traits::AdjacencyMatrixGraph as AdjacencyMatrixGraphTrait;
+pub use traits::VertexIndexGraph as VertexIndexGraphTrait;
+pub use traits::EdgeIndexGraph as EdgeIndexGraphTrait;
+pub use traits::UndirectedGraph as UndirectedGraphTrait;
+pub use traits::{Direction,DirectedEdges,UndirectedEdges};
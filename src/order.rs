@@ -1,12 +1,14 @@
-use std::collections::{
-    HashMap
-};
+use std::collections::HashMap;
 use traits::{
     Graph,
     VertexListGraph,
+    VertexIndexGraph,
     IncidenceGraph,
 };
-use std::iter::FromIterator;
+use property_map::{
+    MutablePropertyMap,
+    VertexVecPropertyMap,
+};
 use std::usize;
 use std::fmt::Debug;
 
@@ -17,22 +19,34 @@ pub enum HierarchicalOrdering<T: Clone> {
 }
 
 /// Bourdoncle: "Efficient chaotic iteration strategies with widenings"
-pub fn weak_topo_order<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>>(root: G::Vertex, graph: &'a G) ->  HierarchicalOrdering<G::Vertex> where G::Vertex: Debug{
-    fn visit<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>>(vx: G::Vertex, graph: &'a G, ret: &mut Vec<Box<HierarchicalOrdering<G::Vertex>>>, stack: &mut Vec<G::Vertex>, dfn: &mut HashMap<G::Vertex,usize>, num: &mut usize) -> usize where <G as Graph<'a,V,E>>::Vertex: Debug {
+///
+/// Successors are visited in the order `out_edges` returns them.
+pub fn weak_topo_order<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>>(root: G::Vertex, graph: &'a G) ->  HierarchicalOrdering<G::Vertex> where G::Vertex: Debug {
+    return weak_topo_order_with(root,graph,&mut HashMap::new());
+}
+
+/// `weak_topo_order` keeping the depth first numbers in a vector indexed by `vertex_index`.
+pub fn weak_topo_order_indexed<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E> + VertexIndexGraph<'a,V,E>>(root: G::Vertex, graph: &'a G) ->  HierarchicalOrdering<G::Vertex> where G::Vertex: Debug {
+    return weak_topo_order_with(root,graph,&mut VertexVecPropertyMap::new(graph));
+}
+
+/// Weak topological order with the depth first numbers in `dfn`. Vertices without a number are
+/// unvisited.
+fn weak_topo_order_with<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>, D: MutablePropertyMap<G::Vertex,Value=usize>>(root: G::Vertex, graph: &'a G, dfn: &mut D) ->  HierarchicalOrdering<G::Vertex> where G::Vertex: Debug {
+    fn visit<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>, D: MutablePropertyMap<G::Vertex,Value=usize>>(vx: G::Vertex, graph: &'a G, ret: &mut Vec<Box<HierarchicalOrdering<G::Vertex>>>, stack: &mut Vec<G::Vertex>, dfn: &mut D, num: &mut usize) -> usize where <G as Graph<'a,V,E>>::Vertex: Debug {
         stack.push(vx.clone());
 
         let mut _loop = false;
 
         *num += 1;
-        dfn.insert(vx.clone(),*num);
-        let mut head = dfn[&vx];
+        dfn.put(vx,*num);
+        let mut head = *num;
 
         for e in graph.out_edges(vx) {
-            let succ = graph.target(e);
-            let min = if dfn[&succ] == 0 {
-                visit(succ,graph,ret,stack,dfn,num)
-            } else {
-                dfn[&succ]
+            let succ = graph.opposite(e,vx);
+            let min = match dfn.get(succ) {
+                None | Some(0) => visit(succ,graph,ret,stack,dfn,num),
+                Some(n) => n,
             };
 
             if min <= head {
@@ -41,13 +55,13 @@ pub fn weak_topo_order<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E>
             }
         }
 
-        if head == dfn[&vx] {
-            dfn.insert(vx.clone(),usize::MAX);
+        if Some(head) == dfn.get(vx) {
+            dfn.put(vx,usize::MAX);
             let mut element = stack.pop().unwrap();
 
             if _loop {
                 while element != vx {
-                    dfn.insert(element.clone(),0);
+                    dfn.put(element,0);
                     element = stack.pop().unwrap();
                 }
 
@@ -60,12 +74,12 @@ pub fn weak_topo_order<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E>
         head
     }
 
-    fn component<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>>(vx: G::Vertex, graph: &'a G, stack: &mut Vec<G::Vertex>, dfn: &mut HashMap<G::Vertex,usize>, num: &mut usize) -> HierarchicalOrdering<G::Vertex> where <G as Graph<'a,V,E>>::Vertex: Debug {
+    fn component<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>, D: MutablePropertyMap<G::Vertex,Value=usize>>(vx: G::Vertex, graph: &'a G, stack: &mut Vec<G::Vertex>, dfn: &mut D, num: &mut usize) -> HierarchicalOrdering<G::Vertex> where <G as Graph<'a,V,E>>::Vertex: Debug {
         let mut ret = Vec::<Box<HierarchicalOrdering<G::Vertex>>>::new();
         
         for e in graph.out_edges(vx) {
-            let succ = graph.target(e);
-            if dfn[&succ] == 0 {
+            let succ = graph.opposite(e,vx);
+            if dfn.get(succ).unwrap_or(0) == 0 {
                 visit(succ,graph,&mut ret,stack,dfn,num);
             }
         }
@@ -74,12 +88,11 @@ pub fn weak_topo_order<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E>
         HierarchicalOrdering::Component(ret)
     }
 
-    let mut dfn = HashMap::<G::Vertex,usize>::from_iter(graph.vertices().map(|v| (v,0)));
     let mut num = 0;
     let mut ret = Vec::<Box<HierarchicalOrdering<G::Vertex>>>::new();
     let mut stack = Vec::new();
 
-    visit(root,graph,&mut ret,&mut stack,&mut dfn,&mut num);
+    visit(root,graph,&mut ret,&mut stack,dfn,&mut num);
 
     HierarchicalOrdering::Component(ret)
 }
@@ -134,6 +147,7 @@ mod tests {
         ]);
 
         assert_eq!(weak_topo_order(vx1,&g), expected);
+        assert_eq!(weak_topo_order_indexed(vx1,&g), expected);
     }
 
     #[test]
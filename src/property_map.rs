@@ -0,0 +1,267 @@
+//! External per-vertex and per-edge data.
+//!
+//! Property maps associate values with vertex or edge descriptors without touching the graph's
+//! own labels. `HashMap` works for any graph, the `*VecPropertyMap` types use the dense indices of
+//! `VertexIndexGraph`/`EdgeIndexGraph` and need no hashing. `VertexLabelMap` and `EdgeLabelMap`
+//! expose a projection of the graph labels through the same interface.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use traits::{
+    Graph,
+    VertexIndexGraph,
+    EdgeIndexGraph,
+};
+
+/// Read access to a property. Values are returned by value, `None` means the key has no value.
+pub trait PropertyMap<K> {
+    type Value;
+    fn get(&self,K) -> Option<Self::Value>;
+}
+
+/// Write access to a property.
+pub trait MutablePropertyMap<K>: PropertyMap<K> {
+    /// Sets the value of `key`, returns the previous one.
+    fn put(&mut self,K,Self::Value) -> Option<Self::Value>;
+    /// Removes the value of `key` and returns it.
+    fn clear(&mut self,K) -> Option<Self::Value>;
+}
+
+impl<K: Hash + Eq,T: Clone> PropertyMap<K> for HashMap<K,T> {
+    type Value = T;
+
+    fn get(&self, key: K) -> Option<T> {
+        return HashMap::get(self,&key).cloned();
+    }
+}
+
+impl<K: Hash + Eq,T: Clone> MutablePropertyMap<K> for HashMap<K,T> {
+    fn put(&mut self, key: K, value: T) -> Option<T> {
+        return self.insert(key,value);
+    }
+
+    fn clear(&mut self, key: K) -> Option<T> {
+        return self.remove(&key);
+    }
+}
+
+/// Vertex property stored in a vector indexed by `VertexIndexGraph::vertex_index`.
+pub struct VertexVecPropertyMap<'a,V,E,G: 'a + VertexIndexGraph<'a,V,E>,T> {
+    graph: &'a G,
+    values: Vec<Option<T>>,
+    _marker: PhantomData<(V,E)>,
+}
+
+impl<'a,V,E,G: 'a + VertexIndexGraph<'a,V,E>,T: Clone> VertexVecPropertyMap<'a,V,E,G,T> {
+    /// Empty property map for the vertices of `graph`.
+    pub fn new(graph: &'a G) -> Self {
+        return VertexVecPropertyMap{
+            graph: graph,
+            values: vec![None; graph.vertex_index_bound()],
+            _marker: PhantomData,
+        };
+    }
+
+    /// Property map that assigns `value` to every vertex of `graph`.
+    pub fn with_value(graph: &'a G, value: T) -> Self {
+        return VertexVecPropertyMap{
+            graph: graph,
+            values: vec![Some(value); graph.vertex_index_bound()],
+            _marker: PhantomData,
+        };
+    }
+}
+
+impl<'a,V,E,G: 'a + VertexIndexGraph<'a,V,E>,T: Clone> PropertyMap<G::Vertex> for VertexVecPropertyMap<'a,V,E,G,T> {
+    type Value = T;
+
+    fn get(&self, v: G::Vertex) -> Option<T> {
+        return self.values.get(self.graph.vertex_index(v)).and_then(|x| x.clone());
+    }
+}
+
+impl<'a,V,E,G: 'a + VertexIndexGraph<'a,V,E>,T: Clone> MutablePropertyMap<G::Vertex> for VertexVecPropertyMap<'a,V,E,G,T> {
+    fn put(&mut self, v: G::Vertex, value: T) -> Option<T> {
+        let idx = self.graph.vertex_index(v);
+
+        if idx >= self.values.len() {
+            self.values.resize(idx + 1,None);
+        }
+
+        return self.values[idx].replace(value);
+    }
+
+    fn clear(&mut self, v: G::Vertex) -> Option<T> {
+        return self.values.get_mut(self.graph.vertex_index(v)).and_then(|x| x.take());
+    }
+}
+
+/// Edge property stored in a vector indexed by `EdgeIndexGraph::edge_index`.
+pub struct EdgeVecPropertyMap<'a,V,E,G: 'a + EdgeIndexGraph<'a,V,E>,T> {
+    graph: &'a G,
+    values: Vec<Option<T>>,
+    _marker: PhantomData<(V,E)>,
+}
+
+impl<'a,V,E,G: 'a + EdgeIndexGraph<'a,V,E>,T: Clone> EdgeVecPropertyMap<'a,V,E,G,T> {
+    /// Empty property map for the edges of `graph`.
+    pub fn new(graph: &'a G) -> Self {
+        return EdgeVecPropertyMap{
+            graph: graph,
+            values: vec![None; graph.edge_index_bound()],
+            _marker: PhantomData,
+        };
+    }
+
+    /// Property map that assigns `value` to every edge of `graph`.
+    pub fn with_value(graph: &'a G, value: T) -> Self {
+        return EdgeVecPropertyMap{
+            graph: graph,
+            values: vec![Some(value); graph.edge_index_bound()],
+            _marker: PhantomData,
+        };
+    }
+}
+
+impl<'a,V,E,G: 'a + EdgeIndexGraph<'a,V,E>,T: Clone> PropertyMap<G::Edge> for EdgeVecPropertyMap<'a,V,E,G,T> {
+    type Value = T;
+
+    fn get(&self, e: G::Edge) -> Option<T> {
+        return self.values.get(self.graph.edge_index(e)).and_then(|x| x.clone());
+    }
+}
+
+impl<'a,V,E,G: 'a + EdgeIndexGraph<'a,V,E>,T: Clone> MutablePropertyMap<G::Edge> for EdgeVecPropertyMap<'a,V,E,G,T> {
+    fn put(&mut self, e: G::Edge, value: T) -> Option<T> {
+        let idx = self.graph.edge_index(e);
+
+        if idx >= self.values.len() {
+            self.values.resize(idx + 1,None);
+        }
+
+        return self.values[idx].replace(value);
+    }
+
+    fn clear(&mut self, e: G::Edge) -> Option<T> {
+        return self.values.get_mut(self.graph.edge_index(e)).and_then(|x| x.take());
+    }
+}
+
+/// Read-only property computed from the vertex labels of a graph.
+pub struct VertexLabelMap<'a,V,E,G: 'a + Graph<'a,V,E>,F> {
+    graph: &'a G,
+    projection: F,
+    _marker: PhantomData<(V,E)>,
+}
+
+impl<'a,V,E,G: 'a + Graph<'a,V,E>,T,F: Fn(&V) -> T> VertexLabelMap<'a,V,E,G,F> {
+    pub fn new(graph: &'a G, projection: F) -> Self {
+        return VertexLabelMap{
+            graph: graph,
+            projection: projection,
+            _marker: PhantomData,
+        };
+    }
+}
+
+impl<'a,V,E,G: 'a + Graph<'a,V,E>,T,F: Fn(&V) -> T> PropertyMap<G::Vertex> for VertexLabelMap<'a,V,E,G,F> {
+    type Value = T;
+
+    fn get(&self, v: G::Vertex) -> Option<T> {
+        return self.graph.vertex_label(v).map(&self.projection);
+    }
+}
+
+/// Read-only property computed from the edge labels of a graph.
+pub struct EdgeLabelMap<'a,V,E,G: 'a + Graph<'a,V,E>,F> {
+    graph: &'a G,
+    projection: F,
+    _marker: PhantomData<(V,E)>,
+}
+
+impl<'a,V,E,G: 'a + Graph<'a,V,E>,T,F: Fn(&E) -> T> EdgeLabelMap<'a,V,E,G,F> {
+    pub fn new(graph: &'a G, projection: F) -> Self {
+        return EdgeLabelMap{
+            graph: graph,
+            projection: projection,
+            _marker: PhantomData,
+        };
+    }
+}
+
+impl<'a,V,E,G: 'a + Graph<'a,V,E>,T,F: Fn(&E) -> T> PropertyMap<G::Edge> for EdgeLabelMap<'a,V,E,G,F> {
+    type Value = T;
+
+    fn get(&self, e: G::Edge) -> Option<T> {
+        return self.graph.edge_label(e).map(&self.projection);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adjacency_list::{
+        AdjacencyList,
+        AdjacencyListVertexDescriptor,
+        AdjacencyListEdgeDescriptor,
+    };
+    use traits::{
+        MutableGraph,
+        IncidenceGraph,
+    };
+
+    // Relaxes all out edges of `v` using whatever property maps it is given.
+    fn relax<W: PropertyMap<AdjacencyListEdgeDescriptor,Value=usize>,P: PropertyMap<AdjacencyListVertexDescriptor,Value=usize>,D: MutablePropertyMap<AdjacencyListVertexDescriptor,Value=usize>>(v: AdjacencyListVertexDescriptor, g: &AdjacencyList<&'static str,usize>, weights: &W, prio: &P, dist: &mut D) {
+        let d = dist.get(v).unwrap();
+
+        for e in g.out_edges(v) {
+            let w = g.target(e);
+            let nd = d + weights.get(e).unwrap() + prio.get(w).unwrap();
+
+            if dist.get(w).is_none_or(|x| nd < x) {
+                dist.put(w,nd);
+            }
+        }
+    }
+
+    #[test]
+    fn maps() {
+        let mut g = AdjacencyList::<&'static str,usize>::new();
+        let a = g.add_vertex("a");
+        let b = g.add_vertex("bb");
+        let c = g.add_vertex("ccc");
+        let ab = g.add_edge(1,a,b).unwrap();
+        let ac = g.add_edge(5,a,c).unwrap();
+
+        let weights = EdgeLabelMap::new(&g,|x: &usize| *x);
+        let lengths = VertexLabelMap::new(&g,|x: &&'static str| x.len());
+
+        assert_eq!(weights.get(ab), Some(1));
+        assert_eq!(lengths.get(c), Some(3));
+
+        let mut dist = VertexVecPropertyMap::new(&g);
+        dist.put(a,0);
+        relax(a,&g,&weights,&lengths,&mut dist);
+
+        assert_eq!(dist.get(a), Some(0));
+        assert_eq!(dist.get(b), Some(3));
+        assert_eq!(dist.get(c), Some(8));
+        assert_eq!(dist.clear(c), Some(8));
+        assert_eq!(dist.get(c), None);
+
+        let mut dist = HashMap::new();
+        dist.put(a,0);
+        relax(a,&g,&weights,&lengths,&mut dist);
+
+        assert_eq!(PropertyMap::get(&dist,b), Some(3));
+        assert_eq!(PropertyMap::get(&dist,c), Some(8));
+
+        let mut color = EdgeVecPropertyMap::with_value(&g,"black");
+
+        assert_eq!(color.put(ac,"red"), Some("black"));
+        assert_eq!(color.get(ab), Some("black"));
+        assert_eq!(color.get(ac), Some("red"));
+    }
+}
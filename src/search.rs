@@ -1,50 +1,98 @@
-use std::collections::{
-    HashSet,
-    HashMap
-};
 use traits::{
     Graph,
     VertexListGraph,
+    VertexIndexGraph,
     IncidenceGraph,
     BidirectionalGraph,
 };
+use property_map::{
+    PropertyMap,
+    MutablePropertyMap,
+    VertexVecPropertyMap,
+};
+use std::collections::{HashMap,VecDeque};
+use std::hash::Hash;
 
 #[derive(Clone,Copy,PartialEq)]
 pub enum TraversalOrder {
     Preorder,
     Postorder,
+    /// Vertices in order of their hop distance from the start vertex.
+    BreadthFirst,
 }
 
-pub struct TreeIterator<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>> {
+/// Depth or breadth first traversal starting at a single vertex. Successors are visited in the
+/// order `out_edges` returns them. Visited vertices are kept in `S`, a hash map by default or a
+/// vector for graphs created with `new_indexed`.
+pub struct TreeIterator<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>, S = HashMap<<G as Graph<'a,V,E>>::Vertex,()>> {
     order: TraversalOrder,
     stack: Vec<G::Vertex>,
-    seen: HashSet<G::Vertex>,
+    queue: VecDeque<G::Vertex>,
+    seen: S,
     position: G::Vertex,
     graph: &'a G
 }
 
 impl<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>> TreeIterator<'a,V,E,G> {
     pub fn new(start: G::Vertex, order: TraversalOrder, g: &'a G) -> Self {
+        return TreeIterator::with_seen(start,order,g,HashMap::new());
+    }
+}
+
+impl<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E> + VertexIndexGraph<'a,V,E>> TreeIterator<'a,V,E,G,VertexVecPropertyMap<'a,V,E,G,()>> {
+    /// Traversal that keeps the visited vertices in a vector indexed by `vertex_index`.
+    pub fn new_indexed(start: G::Vertex, order: TraversalOrder, g: &'a G) -> Self {
+        return TreeIterator::with_seen(start,order,g,VertexVecPropertyMap::new(g));
+    }
+}
+
+impl<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>, S: MutablePropertyMap<G::Vertex,Value=()>> TreeIterator<'a,V,E,G,S> {
+    fn with_seen(start: G::Vertex, order: TraversalOrder, g: &'a G, mut seen: S) -> Self {
+        let mut queue = VecDeque::new();
+
+        if order == TraversalOrder::BreadthFirst {
+            seen.put(start,());
+            queue.push_back(start);
+        }
+
         TreeIterator{
             order: order,
             stack: if order == TraversalOrder::Postorder { vec![start] } else { vec![] },
-            seen: HashSet::<_>::new(),
+            queue: queue,
+            seen: seen,
             position: start,
             graph: g
         }
     }
 
+    /// Marks `vx` as visited, returns false if it already was.
+    fn visit(&mut self, vx: G::Vertex) -> bool {
+        return self.seen.put(vx,()).is_none();
+    }
+
+    fn next_breadth_first(&mut self) -> Option<G::Vertex> {
+        let vx = self.queue.pop_front()?;
+
+        for out in self.graph.out_edges(vx) {
+            let wx = self.graph.opposite(out,vx);
+            if self.visit(wx) {
+                self.queue.push_back(wx);
+            }
+        }
+
+        return Some(vx);
+    }
+
     fn next_preorder(&mut self) -> Option<G::Vertex> {
-        if !self.seen.contains(&self.position) {
-            self.seen.insert(self.position);
+        let position = self.position;
 
+        if self.visit(position) {
             return Some(self.position)
         } else {
             loop {
                 for out in self.graph.out_edges(self.position) {
-                    let vx = self.graph.target(out);
-                    if !self.seen.contains(&vx) {
-                        self.seen.insert(vx);
+                    let vx = self.graph.opposite(out,self.position);
+                    if self.visit(vx) {
                         self.stack.push(self.position);
                         self.position = vx;
                         return Some(vx);
@@ -64,9 +112,8 @@ impl<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<
         loop {
             let mut cont = false;
             for out in self.graph.out_edges(self.position) {
-                let vx = self.graph.target(out);
-                if !self.seen.contains(&vx) {
-                    self.seen.insert(vx);
+                let vx = self.graph.opposite(out,self.position);
+                if self.visit(vx) {
                     self.stack.push(self.position);
                     self.position = vx;
                     cont = true;
@@ -89,18 +136,28 @@ impl<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<
     }
 }
 
-impl<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>> Iterator for TreeIterator<'a,V,E,G> {
+impl<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>, S: MutablePropertyMap<G::Vertex,Value=()>> Iterator for TreeIterator<'a,V,E,G,S> {
     type Item = G::Vertex;
     fn next(&mut self) -> Option<G::Vertex> {
         match self.order {
             TraversalOrder::Preorder => self.next_preorder(),
             TraversalOrder::Postorder => self.next_postorder(),
+            TraversalOrder::BreadthFirst => self.next_breadth_first(),
         }
     }
 }
 
 pub fn is_connected<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E>>(graph: &'a G) -> bool {
-    let mut seen = HashSet::<G::Vertex>::new();
+    return is_connected_with(graph,HashMap::new());
+}
+
+/// `is_connected` keeping the visited vertices in a vector indexed by `vertex_index`.
+pub fn is_connected_indexed<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E> + VertexIndexGraph<'a,V,E>>(graph: &'a G) -> bool {
+    return is_connected_with(graph,VertexVecPropertyMap::new(graph));
+}
+
+fn is_connected_with<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + BidirectionalGraph<'a,V,E> + VertexListGraph<'a,V,E>, S: MutablePropertyMap<G::Vertex,Value=()>>(graph: &'a G, mut seen: S) -> bool {
+    let mut num_seen = 0;
 
     if let Some(s) = graph.vertices().next() {
         let mut stack = vec![s];
@@ -108,19 +165,21 @@ pub fn is_connected<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + B
         while !stack.is_empty() {
             let vx = stack.pop().unwrap().clone();
 
-            seen.insert(vx);
-            let ed = graph.out_edges(vx).map(|out| graph.target(out)).chain(
-                graph.in_edges(vx).map(|_in| graph.source(_in))).collect::<Vec<_>>();
+            if seen.put(vx,()).is_none() {
+                num_seen += 1;
+            }
+            let ed = graph.out_edges(vx).map(|out| graph.opposite(out,vx)).chain(
+                graph.in_edges(vx).map(|_in| graph.opposite(_in,vx))).collect::<Vec<_>>();
 
             for s in ed {
-                if !seen.contains(&s) {
+                if seen.get(s).is_none() {
                     stack.push(s);
                 }
             }
         }
 
-        assert!(seen.len() <= graph.num_vertices());
-        seen.len() == graph.num_vertices()
+        assert!(num_seen <= graph.num_vertices());
+        num_seen == graph.num_vertices()
     } else {
         true
     }
@@ -139,36 +198,49 @@ pub enum VertexEvent {
     Finished,
 }
 
-#[derive(PartialEq,Eq,Hash,Debug)]
+#[derive(PartialEq,Eq,Hash,Debug,Clone,Copy)]
 pub enum VertexColor {
     White,
     Gray,
     Black,
 }
 
+/// Recursive depth first search from `start`. Successors are visited in the order `out_edges`
+/// returns them.
 pub fn depth_first_visit<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>>(vertex_visitor: &mut FnMut(&G::Vertex,VertexEvent),
                      edge_visitor: &mut FnMut(&G::Edge,EdgeKind),
                      start: &G::Vertex,
                      graph: &'a G) {
-    let mut color = HashMap::new();
+    depth_first_visit_with(vertex_visitor,edge_visitor,start,graph,&mut HashMap::new());
+}
 
-    for v in graph.vertices() {
-        color.insert(v,VertexColor::White);
-    }
+/// `depth_first_visit` keeping the vertex colors in a vector indexed by `vertex_index`.
+pub fn depth_first_visit_indexed<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E> + VertexIndexGraph<'a,V,E>>(vertex_visitor: &mut FnMut(&G::Vertex,VertexEvent),
+                     edge_visitor: &mut FnMut(&G::Edge,EdgeKind),
+                     start: &G::Vertex,
+                     graph: &'a G) {
+    depth_first_visit_with(vertex_visitor,edge_visitor,start,graph,&mut VertexVecPropertyMap::new(graph));
+}
 
-    fn visit<'a,V,E,G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>>(vx: &G::Vertex,
-             color: &mut HashMap<G::Vertex,VertexColor>,
+/// Depth first search with the colors in `color`. Vertices without a color are white.
+fn depth_first_visit_with<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>, C: MutablePropertyMap<G::Vertex,Value=VertexColor>>(vertex_visitor: &mut FnMut(&G::Vertex,VertexEvent),
+                     edge_visitor: &mut FnMut(&G::Edge,EdgeKind),
+                     start: &G::Vertex,
+                     graph: &'a G,
+                     color: &mut C) {
+    fn visit<'a,V,E,G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>,C: MutablePropertyMap<G::Vertex,Value=VertexColor>>(vx: &G::Vertex,
+             color: &mut C,
              vertex_visitor: &mut FnMut(&G::Vertex,VertexEvent),
              edge_visitor: &mut FnMut(&G::Edge,EdgeKind),
              graph: &'a G) {
-        color.insert(vx.clone(),VertexColor::Gray);
+        color.put(*vx,VertexColor::Gray);
 
         vertex_visitor(&vx,VertexEvent::Discovered);
 
         for e in graph.out_edges(vx.clone()) {
-            let wx = graph.target(e);
+            let wx = graph.opposite(e,*vx);
 
-            match color[&wx] {
+            match color.get(wx).unwrap_or(VertexColor::White) {
                 VertexColor::White => {
                     edge_visitor(&e,EdgeKind::Tree);
                     visit(&wx,color,vertex_visitor,edge_visitor,graph);
@@ -178,11 +250,150 @@ pub fn depth_first_visit<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E
             }
         }
 
-        color.insert(vx.clone(),VertexColor::Black);
+        color.put(*vx,VertexColor::Black);
         vertex_visitor(&vx,VertexEvent::Finished);
     }
 
-    visit::<V,E,G>(start,&mut color,vertex_visitor,edge_visitor,graph);
+    visit::<V,E,G,C>(start,color,vertex_visitor,edge_visitor,graph);
+}
+
+/// Events of `breadth_first_visit` for vertices.
+#[derive(PartialEq,Eq,Debug)]
+pub enum BfsVertexEvent {
+    /// The vertex was reached for the first time and put into the queue.
+    Discovered,
+    /// The vertex was taken out of the queue, its out edges are examined next.
+    Examined,
+    /// All out edges of the vertex were examined.
+    Finished,
+}
+
+/// Events of `breadth_first_visit` for edges. Every out edge of an examined vertex is reported
+/// as `Examined`, followed by `Tree` if it discovered its target and by `NonTree` and
+/// `GrayTarget` or `BlackTarget` otherwise.
+#[derive(PartialEq,Eq,Debug)]
+pub enum BfsEdgeEvent {
+    Examined,
+    Tree,
+    NonTree,
+    /// The target is discovered but not finished yet.
+    GrayTarget,
+    /// The target is finished.
+    BlackTarget,
+}
+
+/// Result of `breadth_first_visit`. `L` and `P` are property maps of the vertices, hash maps or
+/// for `breadth_first_visit_indexed` vectors.
+#[derive(Clone,Debug,PartialEq)]
+pub struct BreadthFirstTree<L,P> {
+    /// Hop distance from the start vertex of every reachable vertex.
+    pub levels: L,
+    /// Parent and tree edge of every reachable vertex except the start vertex.
+    pub predecessors: P,
+}
+
+impl<L,P> BreadthFirstTree<L,P> {
+    /// Vertices of a shortest path from the start vertex to `vx`, `None` if `vx` is not
+    /// reachable.
+    pub fn path<Vx: Copy,Ed>(&self, vx: Vx) -> Option<Vec<Vx>>
+        where L: PropertyMap<Vx,Value=usize>, P: PropertyMap<Vx,Value=(Vx,Ed)> {
+        self.levels.get(vx)?;
+
+        let mut ret = vec![vx];
+        let mut cur = vx;
+
+        while let Some((parent,_)) = self.predecessors.get(cur) {
+            ret.push(parent);
+            cur = parent;
+        }
+
+        ret.reverse();
+        return Some(ret);
+    }
+}
+
+/// Breadth first search from `start`, reporting BGL-style events. Successors are visited in the
+/// order `out_edges` returns them. Returns the hop distances and the BFS tree of all vertices
+/// reachable from `start`.
+pub fn breadth_first_visit<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>>(vertex_visitor: &mut FnMut(&G::Vertex,BfsVertexEvent),
+                     edge_visitor: &mut FnMut(&G::Edge,BfsEdgeEvent),
+                     start: &G::Vertex,
+                     graph: &'a G) -> BreadthFirstTree<HashMap<G::Vertex,usize>,HashMap<G::Vertex,(G::Vertex,G::Edge)>> {
+    let mut ret = BreadthFirstTree{
+        levels: HashMap::new(),
+        predecessors: HashMap::new(),
+    };
+
+    breadth_first_visit_with(vertex_visitor,edge_visitor,start,graph,&mut HashMap::new(),&mut ret);
+    return ret;
+}
+
+/// `breadth_first_visit` keeping the colors and the result in vectors indexed by `vertex_index`.
+pub fn breadth_first_visit_indexed<'a, V, E, G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E> + VertexIndexGraph<'a,V,E>>(vertex_visitor: &mut FnMut(&G::Vertex,BfsVertexEvent),
+                     edge_visitor: &mut FnMut(&G::Edge,BfsEdgeEvent),
+                     start: &G::Vertex,
+                     graph: &'a G) -> BreadthFirstTree<VertexVecPropertyMap<'a,V,E,G,usize>,VertexVecPropertyMap<'a,V,E,G,(G::Vertex,G::Edge)>> {
+    let mut ret = BreadthFirstTree{
+        levels: VertexVecPropertyMap::new(graph),
+        predecessors: VertexVecPropertyMap::new(graph),
+    };
+
+    breadth_first_visit_with(vertex_visitor,edge_visitor,start,graph,&mut VertexVecPropertyMap::new(graph),&mut ret);
+    return ret;
+}
+
+/// Breadth first search with the colors in `color` and the result in `tree`. Vertices without a
+/// color are white.
+fn breadth_first_visit_with<'a, V, E, G, C, L, P>(vertex_visitor: &mut FnMut(&G::Vertex,BfsVertexEvent),
+                     edge_visitor: &mut FnMut(&G::Edge,BfsEdgeEvent),
+                     start: &G::Vertex,
+                     graph: &'a G,
+                     color: &mut C,
+                     tree: &mut BreadthFirstTree<L,P>)
+    where G: 'a + Graph<'a,V,E> + IncidenceGraph<'a,V,E> + VertexListGraph<'a,V,E>,
+          C: MutablePropertyMap<G::Vertex,Value=VertexColor>,
+          L: MutablePropertyMap<G::Vertex,Value=usize>,
+          P: MutablePropertyMap<G::Vertex,Value=(G::Vertex,G::Edge)> {
+    let mut queue = VecDeque::new();
+
+    color.put(*start,VertexColor::Gray);
+    tree.levels.put(*start,0);
+    vertex_visitor(start,BfsVertexEvent::Discovered);
+    queue.push_back(*start);
+
+    while let Some(vx) = queue.pop_front() {
+        let level = tree.levels.get(vx).unwrap();
+
+        vertex_visitor(&vx,BfsVertexEvent::Examined);
+
+        for e in graph.out_edges(vx) {
+            let wx = graph.opposite(e,vx);
+
+            edge_visitor(&e,BfsEdgeEvent::Examined);
+
+            match color.get(wx).unwrap_or(VertexColor::White) {
+                VertexColor::White => {
+                    edge_visitor(&e,BfsEdgeEvent::Tree);
+                    color.put(wx,VertexColor::Gray);
+                    tree.levels.put(wx,level + 1);
+                    tree.predecessors.put(wx,(vx,e));
+                    vertex_visitor(&wx,BfsVertexEvent::Discovered);
+                    queue.push_back(wx);
+                }
+                VertexColor::Gray => {
+                    edge_visitor(&e,BfsEdgeEvent::NonTree);
+                    edge_visitor(&e,BfsEdgeEvent::GrayTarget);
+                }
+                VertexColor::Black => {
+                    edge_visitor(&e,BfsEdgeEvent::NonTree);
+                    edge_visitor(&e,BfsEdgeEvent::BlackTarget);
+                }
+            }
+        }
+
+        color.put(vx,VertexColor::Black);
+        vertex_visitor(&vx,BfsVertexEvent::Finished);
+    }
 }
 
 #[cfg(test)]
@@ -216,6 +427,7 @@ mod tests {
             g.add_edge((),vx5,vx1);
 
             assert!(is_connected(&g));
+            assert!(is_connected_indexed(&g));
         }
 
         {
@@ -237,6 +449,7 @@ mod tests {
             g.add_edge((),vx5,vx4);
 
             assert!(!is_connected(&g));
+            assert!(!is_connected_indexed(&g));
         }
     }
 
@@ -309,6 +522,112 @@ mod tests {
         let expect = vec!["f","b","a","d","c","e","g","i","h"].iter().map(|x| x.to_string()).collect::<Vec<_>>();
 
         assert_eq!(preorder,expect);
+
+        let indexed = TreeIterator::new_indexed(f,TraversalOrder::Preorder,&tree).collect::<Vec<_>>();
+        assert_eq!(indexed, TreeIterator::new(f,TraversalOrder::Preorder,&tree).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn successor_order() {
+        let mut g = AdjacencyList::<&'static str,()>::with_ordered_edges();
+        let a = g.add_vertex("a");
+        let b = g.add_vertex("b");
+        let c = g.add_vertex("c");
+        let d = g.add_vertex("d");
+
+        let ab = g.add_edge((),a,b).unwrap();
+        let ac = g.add_edge((),a,c).unwrap();
+        let ad = g.add_edge((),a,d).unwrap();
+
+        g.remove_edge(ab);
+
+        let labels = |g: &AdjacencyList<&'static str,()>| {
+            TreeIterator::new(a,TraversalOrder::Preorder,g).map(|vx| *g.vertex_label(vx).unwrap()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(labels(&g), vec!["a","c","d"]);
+
+        let ab = g.insert_edge_at((),a,b,1).unwrap();
+        assert_eq!(labels(&g), vec!["a","c","b","d"]);
+
+        g.reorder_out_edges(a,&[ad,ab,ac]).unwrap();
+        assert_eq!(labels(&g), vec!["a","d","b","c"]);
+
+        let mut finished = vec![];
+        depth_first_visit(&mut |&vx,ev| if ev == VertexEvent::Finished { finished.push(vx) },&mut |_,_| {},&a,&g);
+        assert_eq!(finished, vec![d,b,c,a]);
+
+        let mut finished = vec![];
+        depth_first_visit_indexed(&mut |&vx,ev| if ev == VertexEvent::Finished { finished.push(vx) },&mut |_,_| {},&a,&g);
+        assert_eq!(finished, vec![d,b,c,a]);
+    }
+
+    #[test]
+    fn breadth_first() {
+        let mut g = AdjacencyList::<&'static str,()>::new();
+        let a = g.add_vertex("a");
+        let b = g.add_vertex("b");
+        let c = g.add_vertex("c");
+        let d = g.add_vertex("d");
+        let e = g.add_vertex("e");
+        let f = g.add_vertex("f");
+
+        let ab = g.add_edge((),a,b).unwrap();
+        let ac = g.add_edge((),a,c).unwrap();
+        let bd = g.add_edge((),b,d).unwrap();
+        let cd = g.add_edge((),c,d).unwrap();
+        let da = g.add_edge((),d,a).unwrap();
+        let de = g.add_edge((),d,e).unwrap();
+        let cc = g.add_edge((),c,c).unwrap();
+
+        let order = TreeIterator::new(a,TraversalOrder::BreadthFirst,&g).map(|vx| *g.vertex_label(vx).unwrap()).collect::<Vec<_>>();
+        assert_eq!(order, vec!["a","b","c","d","e"]);
+
+        let mut vertex_events = vec![];
+        let mut edge_events = vec![];
+        let tree = breadth_first_visit(
+            &mut |&vx,ev| vertex_events.push((*g.vertex_label(vx).unwrap(),ev)),
+            &mut |&ed,ev| if ev != BfsEdgeEvent::Examined { edge_events.push((ed,ev)) },
+            &a,
+            &g);
+
+        assert_eq!(tree.levels[&a], 0);
+        assert_eq!(tree.levels[&c], 1);
+        assert_eq!(tree.levels[&e], 3);
+        assert!(!tree.levels.contains_key(&f));
+        assert_eq!(tree.predecessors[&d], (b,bd));
+        assert!(!tree.predecessors.contains_key(&a));
+        assert_eq!(tree.path(e), Some(vec![a,b,d,e]));
+        assert_eq!(tree.path(a), Some(vec![a]));
+        assert_eq!(tree.path(f), None);
+
+        let indexed = breadth_first_visit_indexed(&mut |_,_| {},&mut |_,_| {},&a,&g);
+
+        for vx in g.vertices() {
+            assert_eq!(indexed.levels.get(vx), tree.levels.get(&vx).cloned());
+            assert_eq!(indexed.predecessors.get(vx), tree.predecessors.get(&vx).cloned());
+            assert_eq!(indexed.path(vx), tree.path(vx));
+        }
+
+        assert_eq!(&vertex_events[0..4], &[
+            ("a",BfsVertexEvent::Discovered),
+            ("a",BfsVertexEvent::Examined),
+            ("b",BfsVertexEvent::Discovered),
+            ("c",BfsVertexEvent::Discovered),
+        ]);
+        assert_eq!(vertex_events.iter().filter(|x| x.1 == BfsVertexEvent::Finished).map(|x| x.0).collect::<Vec<_>>(), vec!["a","b","c","d","e"]);
+        assert_eq!(edge_events, vec![
+            (ab,BfsEdgeEvent::Tree),
+            (ac,BfsEdgeEvent::Tree),
+            (bd,BfsEdgeEvent::Tree),
+            (cd,BfsEdgeEvent::NonTree),
+            (cd,BfsEdgeEvent::GrayTarget),
+            (cc,BfsEdgeEvent::NonTree),
+            (cc,BfsEdgeEvent::GrayTarget),
+            (da,BfsEdgeEvent::NonTree),
+            (da,BfsEdgeEvent::BlackTarget),
+            (de,BfsEdgeEvent::Tree),
+        ]);
     }
 
     #[test]
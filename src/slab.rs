@@ -0,0 +1,316 @@
+//! Vec-backed storage with free-list reuse and generation counters.
+//!
+//! Every slot carries a generation that is bumped when its value is removed. Keys are
+//! `(index, generation)` pairs, so a key of a removed value never matches a later value that
+//! reuses the same slot.
+
+use std::iter::Enumerate;
+use std::slice;
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+struct Entry<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+pub struct Slab<T> {
+    entries: Vec<Entry<T>>,
+    free: Vec<usize>,
+    len: usize,
+    /// Generation of slots appended past the end. Raised by `compact` so that keys of truncated
+    /// slots stay invalid.
+    base: u32,
+}
+
+impl<T> Slab<T> {
+    pub fn new() -> Self {
+        return Slab {
+            entries: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            base: 0,
+        };
+    }
+
+    /// Slab holding the values under the given `(index, generation, value)` keys and the free
+    /// `(index, generation)` slots in the order of `free_slots`. Slots appended later get
+    /// generation `base`. Returns `None` unless every index below the number of values and free
+    /// slots is given exactly once.
+    pub fn from_entries<I: IntoIterator<Item=(usize,u32,T)>>(values: I, free: Vec<(usize,u32)>, base: u32) -> Option<Self> {
+        let values = values.into_iter().collect::<Vec<_>>();
+        let len = values.len();
+        let mut entries = (0..len + free.len()).map(|_| None).collect::<Vec<Option<Entry<T>>>>();
+        let free_entries = free.iter().map(|&(idx,generation)| (idx,generation,None));
+
+        for (idx,generation,value) in values.into_iter().map(|(idx,generation,x)| (idx,generation,Some(x))).chain(free_entries) {
+            match entries.get_mut(idx) {
+                Some(slot) if slot.is_none() => *slot = Some(Entry{ generation: generation, value: value }),
+                _ => return None,
+            }
+        }
+
+        // all slots are set, there are as many keys as slots and none repeats
+        let entries = entries.into_iter().map(|x| x.unwrap()).collect();
+        let free = free.into_iter().map(|(idx,_)| idx).collect();
+
+        return Some(Slab{ entries: entries, free: free, len: len, base: base });
+    }
+
+    /// Unoccupied slots with the generation of their next value, the last one is reused first.
+    pub fn free_slots(&self) -> Vec<(usize,u32)> {
+        return self.free.iter().map(|&idx| (idx,self.entries[idx].generation)).collect();
+    }
+
+    /// Generation of slots appended past the end.
+    pub fn base(&self) -> u32 {
+        return self.base;
+    }
+
+    /// Number of occupied slots.
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    /// Upper bound of all slot indices, occupied or not.
+    pub fn bound(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Makes room for `additional` more values without reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional.saturating_sub(self.free.len()));
+    }
+
+    pub fn insert(&mut self, value: T) -> (usize,u32) {
+        self.len += 1;
+
+        if let Some(idx) = self.free.pop() {
+            let entry = &mut self.entries[idx];
+
+            entry.value = Some(value);
+            return (idx,entry.generation);
+        }
+
+        self.entries.push(Entry{ generation: self.base, value: Some(value) });
+        return (self.entries.len() - 1,self.base);
+    }
+
+    pub fn remove(&mut self, idx: usize, generation: u32) -> Option<T> {
+        let ret = match self.entries.get_mut(idx) {
+            Some(entry) if entry.generation == generation => entry.value.take(),
+            _ => None,
+        };
+
+        if ret.is_some() {
+            self.entries[idx].generation = generation.wrapping_add(1);
+            self.free.push(idx);
+            self.len -= 1;
+        }
+
+        return ret;
+    }
+
+    pub fn get(&self, idx: usize, generation: u32) -> Option<&T> {
+        match self.entries.get(idx) {
+            Some(entry) if entry.generation == generation => entry.value.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, idx: usize, generation: u32) -> Option<&mut T> {
+        match self.entries.get_mut(idx) {
+            Some(entry) if entry.generation == generation => entry.value.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Slab with the same keys holding `f` applied to the values of this one. Values for which `f`
+    /// returns `None` are removed, their keys stay invalid in the new slab.
+    pub fn filter_map<U,F: FnMut(usize,u32,&T) -> Option<U>>(&self, mut f: F) -> Slab<U> {
+        let mut free = self.free.clone();
+        let mut len = 0;
+        let mut entries = Vec::with_capacity(self.entries.len());
+
+        for (idx,entry) in self.entries.iter().enumerate() {
+            let value = entry.value.as_ref().map(|x| f(idx,entry.generation,x));
+
+            match value {
+                Some(Some(x)) => {
+                    len += 1;
+                    entries.push(Entry{ generation: entry.generation, value: Some(x) });
+                }
+                Some(None) => {
+                    free.push(idx);
+                    entries.push(Entry{ generation: entry.generation.wrapping_add(1), value: None });
+                }
+                None => {
+                    entries.push(Entry{ generation: entry.generation, value: None });
+                }
+            }
+        }
+
+        return Slab{ entries: entries, free: free, len: len, base: self.base };
+    }
+
+    /// Moves all values to the front, keeping their order. Returns the new key of every old index
+    /// that held a value. The new keys use a generation above all previous ones, so no key from
+    /// before the call matches a value afterwards.
+    pub fn compact(&mut self) -> Vec<Option<(usize,u32)>> {
+        let generation = self.entries.iter().map(|x| x.generation).fold(self.base,|a,b| a.max(b)).wrapping_add(1);
+        let mut remap = Vec::with_capacity(self.entries.len());
+        let mut entries = Vec::with_capacity(self.len);
+
+        for entry in self.entries.drain(..) {
+            if let Some(value) = entry.value {
+                remap.push(Some((entries.len(),generation)));
+                entries.push(Entry{ generation: generation, value: Some(value) });
+            } else {
+                remap.push(None);
+            }
+        }
+
+        self.entries = entries;
+        self.free.clear();
+        self.base = generation;
+        return remap;
+    }
+
+    /// Iterates all occupied slots in index order.
+    pub fn iter(&self) -> Iter<'_,T> {
+        return Iter{ inner: self.entries.iter().enumerate(), remaining: self.len };
+    }
+}
+
+#[derive(Debug)]
+pub struct Iter<'a,T: 'a> {
+    inner: Enumerate<slice::Iter<'a,Entry<T>>>,
+    remaining: usize,
+}
+
+impl<'a,T> Iterator for Iter<'a,T> {
+    type Item = (usize,u32,&'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (idx,entry) in self.inner.by_ref() {
+            if let Some(ref value) = entry.value {
+                self.remaining -= 1;
+                return Some((idx,entry.generation,value));
+            }
+        }
+
+        return None;
+    }
+
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        return (self.remaining,Some(self.remaining));
+    }
+}
+
+impl<'a,T> ExactSizeIterator for Iter<'a,T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuse() {
+        let mut s = Slab::<&'static str>::new();
+        let a = s.insert("a");
+        let b = s.insert("b");
+
+        assert_eq!(s.len(), 2);
+        assert_eq!(s.remove(a.0,a.1), Some("a"));
+        assert_eq!(s.remove(a.0,a.1), None);
+        assert_eq!(s.get(a.0,a.1), None);
+
+        let c = s.insert("c");
+
+        assert_eq!(c.0, a.0);
+        assert!(c.1 != a.1);
+        assert_eq!(s.get(a.0,a.1), None);
+        assert_eq!(s.get(c.0,c.1), Some(&"c"));
+        assert_eq!(s.iter().map(|(_,_,v)| *v).collect::<Vec<_>>(), vec!["c","b"]);
+        assert_eq!(s.get(b.0,b.1), Some(&"b"));
+    }
+
+    #[test]
+    fn compact() {
+        let mut s = Slab::<&'static str>::new();
+        let a = s.insert("a");
+        let b = s.insert("b");
+        let c = s.insert("c");
+
+        s.remove(a.0,a.1);
+
+        let remap = s.compact();
+
+        assert_eq!(remap, vec![None,Some((0,2)),Some((1,2))]);
+        assert_eq!(s.len(), 2);
+        assert_eq!(s.bound(), 2);
+        assert_eq!(s.get(0,2), Some(&"b"));
+        assert_eq!(s.get(1,2), Some(&"c"));
+
+        // keys from before the compaction don't match anything
+        assert_eq!(s.get(a.0,a.1), None);
+        assert_eq!(s.get(b.0,b.1), None);
+
+        let d = s.insert("d");
+
+        assert_eq!(d, (2,2));
+        assert!(d != c);
+        assert_eq!(s.get(c.0,c.1), None);
+    }
+
+    #[test]
+    fn from_entries() {
+        let mut s = Slab::from_entries(vec![(3,2,"d"),(1,0,"b")],vec![(2,1),(0,4)],7).unwrap();
+
+        assert_eq!(s.len(), 2);
+        assert_eq!(s.bound(), 4);
+        assert_eq!(s.get(3,2), Some(&"d"));
+        assert_eq!(s.get(3,0), None);
+        assert_eq!(s.iter().map(|(i,_,v)| (i,*v)).collect::<Vec<_>>(), vec![(1,"b"),(3,"d")]);
+        assert_eq!(s.free_slots(), vec![(2,1),(0,4)]);
+        assert_eq!(s.insert("a"), (0,4));
+        assert_eq!(s.insert("c"), (2,1));
+        assert_eq!(s.insert("e"), (4,7));
+
+        // round trip
+        s.remove(1,0);
+
+        let t = Slab::from_entries(s.iter().map(|(i,g,&v)| (i,g,v)),s.free_slots(),s.base()).unwrap();
+
+        assert_eq!(t.free_slots(), vec![(1,1)]);
+        assert_eq!(t.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+
+        // repeated, missing and out of range indices
+        assert!(Slab::from_entries(vec![(0,0,"a"),(0,1,"b")],vec![],0).is_none());
+        assert!(Slab::from_entries(vec![(0,0,"a")],vec![(0,1)],0).is_none());
+        assert!(Slab::from_entries(vec![(1,0,"a")],vec![],0).is_none());
+        assert!(Slab::from_entries(vec![(1 << 40,0,"a")],vec![(0,0)],0).is_none());
+    }
+
+    #[test]
+    fn filter_map() {
+        let mut s = Slab::<usize>::new();
+        let a = s.insert(1);
+        let b = s.insert(2);
+        let c = s.insert(3);
+
+        s.remove(b.0,b.1);
+
+        let t = s.filter_map(|_,_,&x| if x > 1 { Some(x * 10) } else { None });
+
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.get(a.0,a.1), None);
+        assert_eq!(t.get(b.0,b.1), None);
+        assert_eq!(t.get(c.0,c.1), Some(&30));
+
+        let mut t = t;
+        let d = t.insert(4);
+        let e = t.insert(5);
+
+        assert!(d != a && d != b && e != a && e != b);
+    }
+}
@@ -0,0 +1,854 @@
+//! Plain-text graph formats of benchmark collections.
+//!
+//! Supports DIMACS shortest path (`sp`), maximum flow (`max`) and minimum cost flow (`min`)
+//! problems, METIS graph files, Matrix Market coordinate files and whitespace separated edge
+//! lists. Readers process the input line by line and add vertices and edges to any
+//! `MutableGraph` as they go, e.g. an `AdjacencyList`, or an `OwnedAdjacencyMatrix` for dense
+//! data. Vertices are labeled with their number in the file. Writers accept any
+//! `VertexListGraph + EdgeListGraph` and number the vertices in `vertices()` order.
+//!
+//! Edge labels are numeric weights, see `Weight`.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self,BufRead,Write};
+use std::str::FromStr;
+
+use traits::{
+    VertexListGraph,
+    EdgeListGraph,
+    MutableGraph,
+};
+
+/// Edge label of the text formats: a fixed number of numeric fields.
+pub trait Weight: Sized {
+    /// Number of whitespace separated fields.
+    const FIELDS: usize;
+    /// Whether all fields are integers. Decides the field type of Matrix Market files.
+    const INTEGER: bool;
+
+    /// Label from exactly `FIELDS` fields. Returns `None` if a field is malformed.
+    fn parse(fields: &[&str]) -> Option<Self>;
+    /// The `FIELDS` fields of the label.
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Unweighted edges.
+impl Weight for () {
+    const FIELDS: usize = 0;
+    const INTEGER: bool = true;
+
+    fn parse(_: &[&str]) -> Option<Self> {
+        return Some(());
+    }
+
+    fn fields(&self) -> Vec<String> {
+        return vec![];
+    }
+}
+
+macro_rules! number_weight {
+    ($ty:ty,$integer:expr) => {
+        impl Weight for $ty {
+            const FIELDS: usize = 1;
+            const INTEGER: bool = $integer;
+
+            fn parse(fields: &[&str]) -> Option<Self> {
+                return fields[0].parse().ok();
+            }
+
+            fn fields(&self) -> Vec<String> {
+                return vec![self.to_string()];
+            }
+        }
+    }
+}
+
+number_weight!(i32,true);
+number_weight!(i64,true);
+number_weight!(u32,true);
+number_weight!(u64,true);
+number_weight!(usize,true);
+number_weight!(f32,false);
+number_weight!(f64,false);
+
+/// Arc of a DIMACS minimum cost flow problem.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct FlowArc<W> {
+    pub lower: W,
+    pub capacity: W,
+    pub cost: W,
+}
+
+impl<W: Weight> Weight for FlowArc<W> {
+    const FIELDS: usize = 3 * W::FIELDS;
+    const INTEGER: bool = W::INTEGER;
+
+    fn parse(fields: &[&str]) -> Option<Self> {
+        let n = W::FIELDS;
+
+        return Some(FlowArc{
+            lower: W::parse(&fields[0..n])?,
+            capacity: W::parse(&fields[n..2 * n])?,
+            cost: W::parse(&fields[2 * n..3 * n])?,
+        });
+    }
+
+    fn fields(&self) -> Vec<String> {
+        let mut ret = self.lower.fields();
+
+        ret.extend(self.capacity.fields());
+        ret.extend(self.cost.fields());
+        return ret;
+    }
+}
+
+/// Reasons reading a text format can fail.
+#[derive(Debug)]
+pub enum TextError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The input is malformed or the graph rejected an edge. Lines start at 1.
+    Parse{ line: usize, message: String },
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TextError::Io(ref e) => write!(f,"{}",e),
+            TextError::Parse{ line, ref message } => write!(f,"line {}: {}",line,message),
+        }
+    }
+}
+
+impl Error for TextError {}
+
+impl From<io::Error> for TextError {
+    fn from(e: io::Error) -> Self {
+        return TextError::Io(e);
+    }
+}
+
+/// Input split into lines, skipping comments.
+struct Lines<R> {
+    input: R,
+    line: usize,
+    comments: &'static [&'static str],
+}
+
+impl<R: BufRead> Lines<R> {
+    fn new(input: R, comments: &'static [&'static str]) -> Self {
+        return Lines{ input: input, line: 0, comments: comments };
+    }
+
+    /// Next line that is no comment, trimmed. Empty lines are skipped unless `keep_empty` is set.
+    fn next(&mut self, keep_empty: bool) -> Result<Option<String>,TextError> {
+        loop {
+            let mut buf = String::new();
+
+            if self.input.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+
+            self.line += 1;
+
+            let trimmed = buf.trim();
+
+            if self.comments.iter().any(|c| trimmed.starts_with(c)) || (trimmed.is_empty() && !keep_empty) {
+                continue;
+            }
+
+            return Ok(Some(trimmed.to_string()));
+        }
+    }
+
+    fn error<T>(&self, message: String) -> Result<T,TextError> {
+        return Err(TextError::Parse{ line: self.line, message: message });
+    }
+
+    fn number<T: FromStr>(&self, field: &str) -> Result<T,TextError> {
+        match field.parse() {
+            Ok(x) => return Ok(x),
+            Err(_) => return self.error(format!("invalid number {}",field)),
+        }
+    }
+
+    /// Vertex `vertices[n - 1]` for the 1-based vertex number `field`.
+    fn vertex<Vx: Copy>(&self, field: &str, vertices: &[Vx]) -> Result<Vx,TextError> {
+        let n: usize = self.number(field)?;
+
+        if n == 0 || n > vertices.len() {
+            return self.error(format!("vertex {} out of range",n));
+        }
+
+        return Ok(vertices[n - 1]);
+    }
+
+    fn weight<L: Weight>(&self, fields: &[&str]) -> Result<L,TextError> {
+        if fields.len() != L::FIELDS {
+            return self.error(format!("expected {} weight fields, got {}",L::FIELDS,fields.len()));
+        }
+
+        match L::parse(fields) {
+            Some(lb) => return Ok(lb),
+            None => return self.error(format!("invalid weight {}",fields.join(" "))),
+        }
+    }
+
+    /// Adds the vertices `1..n + 1` to `graph`. `n` comes from the input, so no space is reserved
+    /// for it up front.
+    fn add_vertices<'a,L,H: MutableGraph<'a,usize,L>>(&self, graph: &mut H, n: usize) -> Result<Vec<H::Vertex>,TextError> {
+        let end = match n.checked_add(1) {
+            Some(x) => x,
+            None => return self.error(format!("invalid number of vertices {}",n)),
+        };
+        let mut ret = Vec::new();
+
+        for i in 1..end {
+            ret.push(graph.add_vertex(i));
+        }
+
+        return Ok(ret);
+    }
+
+    fn add_edge<'a,L,H: MutableGraph<'a,usize,L>>(&self, graph: &mut H, lb: L, from: H::Vertex, to: H::Vertex) -> Result<H::Edge,TextError> {
+        match graph.try_add_edge(lb,from,to) {
+            Ok(e) => return Ok(e),
+            Err(e) => return self.error(format!("edge rejected: {}",e)),
+        }
+    }
+}
+
+/// Numbers of the vertices of `graph`, starting at `first`, in `vertices()` order.
+fn number_vertices<'a,V,E,G>(graph: &'a G, first: usize) -> HashMap<G::Vertex,usize>
+    where G: 'a + VertexListGraph<'a,V,E> {
+    return graph.vertices().enumerate().map(|(i,v)| (v,i + first)).collect();
+}
+
+fn invalid_input(message: String) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidInput,message);
+}
+
+/// Writes the edge `from to fields...`.
+fn write_edge<W: Write,L: Weight>(out: &mut W, prefix: &str, from: usize, to: usize, lb: &L) -> io::Result<()> {
+    write!(out,"{}{} {}",prefix,from,to)?;
+
+    for f in lb.fields() {
+        write!(out," {}",f)?;
+    }
+
+    return writeln!(out);
+}
+
+/// Kind of a DIMACS problem with the vertices it designates.
+#[derive(Clone,Debug,PartialEq)]
+pub enum DimacsProblem<Vx> {
+    /// `sp`. Edge labels are arc lengths.
+    ShortestPath,
+    /// `max`. Edge labels are capacities.
+    MaxFlow{ source: Vx, sink: Vx },
+    /// `min`. Edge labels are `FlowArc`s. Vertices with nonzero supply are listed with it,
+    /// negative supplies are demands.
+    MinCostFlow{ supplies: Vec<(Vx,i64)> },
+}
+
+/// Reads a DIMACS `sp`, `max` or `min` problem into `graph`.
+///
+/// `L` must have one field for `sp` and `max` problems and three for `min`. Returns the problem and
+/// the vertices by number, `vertices[0]` is vertex 1.
+pub fn read_dimacs<'a,L,H,R>(input: R, graph: &mut H) -> Result<(DimacsProblem<H::Vertex>,Vec<H::Vertex>),TextError>
+    where L: Weight, H: MutableGraph<'a,usize,L>, R: BufRead {
+    let mut lines = Lines::new(input,&["c"]);
+    let mut vertices = Vec::new();
+    let mut kind: Option<String> = None;
+    let mut arcs = 0;
+    let mut expected = 0;
+    let mut sources = vec![];
+    let mut sinks = vec![];
+    let mut supplies = vec![];
+
+    while let Some(line) = lines.next(false)? {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+
+        match (fields[0],&kind) {
+            ("p",&None) => {
+                if fields.len() != 4 {
+                    return lines.error("expected p <problem> <nodes> <arcs>".to_string());
+                }
+
+                let weights = match fields[1] {
+                    "sp" | "max" => 1,
+                    "min" => 3,
+                    p => return lines.error(format!("unsupported problem {}",p)),
+                };
+
+                if weights != L::FIELDS {
+                    return lines.error(format!("{} arcs have {} weight fields, labels have {}",fields[1],weights,L::FIELDS));
+                }
+
+                vertices = lines.add_vertices(graph,lines.number(fields[2])?)?;
+                expected = lines.number(fields[3])?;
+                kind = Some(fields[1].to_string());
+            }
+            ("n",Some(p)) if p == "max" && fields.len() == 3 => {
+                let v = lines.vertex(fields[1],&vertices)?;
+
+                match fields[2] {
+                    "s" => sources.push(v),
+                    "t" => sinks.push(v),
+                    x => return lines.error(format!("expected s or t, got {}",x)),
+                }
+            }
+            ("n",Some(p)) if p == "min" && fields.len() == 3 => {
+                supplies.push((lines.vertex(fields[1],&vertices)?,lines.number(fields[2])?));
+            }
+            ("a",&Some(_)) if fields.len() >= 3 => {
+                let from = lines.vertex(fields[1],&vertices)?;
+                let to = lines.vertex(fields[2],&vertices)?;
+                let lb = lines.weight(&fields[3..])?;
+
+                lines.add_edge(graph,lb,from,to)?;
+                arcs += 1;
+            }
+            _ => return lines.error(format!("unexpected line {}",line)),
+        }
+    }
+
+    let problem = match kind.as_ref().map(|s| &s[..]) {
+        None => return lines.error("missing problem line".to_string()),
+        Some("sp") => DimacsProblem::ShortestPath,
+        Some("max") => {
+            if sources.len() != 1 || sinks.len() != 1 {
+                return lines.error("max problems need exactly one source and one sink".to_string());
+            }
+
+            DimacsProblem::MaxFlow{ source: sources[0], sink: sinks[0] }
+        }
+        Some(_) => DimacsProblem::MinCostFlow{ supplies: supplies },
+    };
+
+    if arcs != expected {
+        return lines.error(format!("expected {} arcs, got {}",expected,arcs));
+    }
+
+    return Ok((problem,vertices));
+}
+
+/// Writes `graph` as DIMACS `problem`. Fails with `InvalidInput` if `L` has the wrong number of
+/// fields for the problem.
+pub fn write_dimacs<'a,V,L,G,W>(graph: &'a G, problem: &DimacsProblem<G::Vertex>, mut out: W) -> io::Result<()>
+    where L: Weight, G: 'a + VertexListGraph<'a,V,L> + EdgeListGraph<'a,V,L>, W: Write {
+    let (name,weights) = match *problem {
+        DimacsProblem::ShortestPath => ("sp",1),
+        DimacsProblem::MaxFlow{ .. } => ("max",1),
+        DimacsProblem::MinCostFlow{ .. } => ("min",3),
+    };
+
+    if L::FIELDS != weights {
+        return Err(invalid_input(format!("{} arcs have {} weight fields, labels have {}",name,weights,L::FIELDS)));
+    }
+
+    let numbers = number_vertices(graph,1);
+    let number = |v: &G::Vertex| numbers.get(v).cloned().ok_or_else(|| invalid_input("unknown vertex".to_string()));
+
+    writeln!(out,"p {} {} {}",name,graph.num_vertices(),graph.num_edges())?;
+
+    match *problem {
+        DimacsProblem::ShortestPath => {}
+        DimacsProblem::MaxFlow{ ref source, ref sink } => {
+            writeln!(out,"n {} s",number(source)?)?;
+            writeln!(out,"n {} t",number(sink)?)?;
+        }
+        DimacsProblem::MinCostFlow{ ref supplies } => {
+            for &(ref v,supply) in supplies.iter() {
+                writeln!(out,"n {} {}",number(v)?,supply)?;
+            }
+        }
+    }
+
+    for e in graph.edges() {
+        write_edge(&mut out,"a ",number(&graph.source(e))?,number(&graph.target(e))?,graph.edge_label(e).unwrap())?;
+    }
+
+    return Ok(());
+}
+
+/// Reads a METIS graph file into `graph`.
+///
+/// METIS graphs are undirected and list every edge at both endpoints with the same weight, each
+/// edge is added once. Files where an edge is missing at one endpoint or has different weights
+/// are rejected.
+/// Read into an `AdjacencyList<usize,L,UndirectedEdges>` to get an undirected graph. Edge weights
+/// need a one-field `L`, files without edge weights can also be read with `()` or assign 1 to
+/// every edge. Returns the vertices by number and the vertex size and weights of every vertex,
+/// in the order they appear in the file.
+pub fn read_metis<'a,L,H,R>(input: R, graph: &mut H) -> Result<(Vec<H::Vertex>,Vec<Vec<i64>>),TextError>
+    where L: Weight, H: MutableGraph<'a,usize,L>, R: BufRead {
+    let mut lines = Lines::new(input,&["%"]);
+    let header = match lines.next(false)? {
+        Some(line) => line,
+        None => return lines.error("missing header".to_string()),
+    };
+    let header = header.split_whitespace().collect::<Vec<_>>();
+
+    if header.len() < 2 || header.len() > 4 {
+        return lines.error("expected <vertices> <edges> [<fmt> [<ncon>]]".to_string());
+    }
+
+    let n = lines.number(header[0])?;
+    let m: usize = lines.number(header[1])?;
+    let fmt = format!("{:0>3}",header.get(2).unwrap_or(&"0"));
+
+    if fmt.len() != 3 || fmt.chars().any(|c| c != '0' && c != '1') {
+        return lines.error(format!("invalid format {}",fmt));
+    }
+
+    let has_size = &fmt[0..1] == "1";
+    let has_weights = &fmt[1..2] == "1";
+    let has_edge_weights = &fmt[2..3] == "1";
+    let ncon: usize = match header.get(3) {
+        Some(x) => lines.number(x)?,
+        None if has_weights => 1,
+        None => 0,
+    };
+    let vertex_fields = match ncon.checked_add(if has_size { 1 } else { 0 }) {
+        Some(x) => x,
+        None => return lines.error(format!("invalid number of vertex weights {}",ncon)),
+    };
+
+    if has_edge_weights && L::FIELDS != 1 {
+        return lines.error(format!("edges have 1 weight field, labels have {}",L::FIELDS));
+    }
+    if !has_edge_weights && L::FIELDS > 1 {
+        return lines.error(format!("edges have no weights, labels have {} fields",L::FIELDS));
+    }
+
+    // vertices are added as their lines are read, so a header count the input doesn't back up
+    // fails instead of allocating
+    let mut vertices = Vec::new();
+    let mut vertex_weights = Vec::new();
+    let mut entries = 0;
+    // weight fields of the edges listed at their lower endpoint only, by endpoints
+    let mut pending = HashMap::<(usize,usize),Vec<Vec<String>>>::new();
+
+    for i in 0..n {
+        let line = match lines.next(true)? {
+            Some(line) => line,
+            None => return lines.error(format!("expected {} vertex lines, got {}",n,i)),
+        };
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let step = if has_edge_weights { 2 } else { 1 };
+
+        if fields.len() < vertex_fields || (fields.len() - vertex_fields) % step != 0 {
+            return lines.error(format!("malformed line of vertex {}",i + 1));
+        }
+
+        vertex_weights.push(fields[..vertex_fields].iter().map(|x| lines.number(x)).collect::<Result<Vec<_>,_>>()?);
+        vertices.push(graph.add_vertex(i + 1));
+
+        for adj in fields[vertex_fields..].chunks(step) {
+            let j: usize = lines.number(adj[0])?;
+
+            if j == 0 || j > n {
+                return lines.error(format!("vertex {} out of range",j));
+            }
+
+            if j == i + 1 {
+                return lines.error(format!("self-loop at vertex {}",j));
+            }
+
+            let lb: L = if has_edge_weights || L::FIELDS == 0 {
+                lines.weight(&adj[1..])?
+            } else {
+                lines.weight(&["1"])?
+            };
+
+            entries += 1;
+
+            if j > i + 1 {
+                pending.entry((i + 1,j)).or_default().push(lb.fields());
+            } else {
+                let fields = lb.fields();
+
+                match pending.get_mut(&(j,i + 1)) {
+                    Some(ref mut weights) if !weights.is_empty() => {
+                        match weights.iter().position(|w| *w == fields) {
+                            Some(pos) => { weights.swap_remove(pos); }
+                            None => return lines.error(format!("edge {} {} has different weights at its endpoints",j,i + 1)),
+                        }
+                    }
+                    _ => return lines.error(format!("edge {} {} is not listed at vertex {}",i + 1,j,j)),
+                }
+
+                lines.add_edge(graph,lb,vertices[j - 1],vertices[i])?;
+            }
+        }
+    }
+
+    if lines.next(false)?.is_some() {
+        return lines.error(format!("more than {} vertex lines",n));
+    }
+
+    if let Some(&(a,b)) = pending.iter().filter(|&(_,w)| !w.is_empty()).map(|(k,_)| k).min() {
+        return lines.error(format!("edge {} {} is not listed at vertex {}",a,b,b));
+    }
+
+    if m.checked_mul(2) != Some(entries) {
+        return lines.error(format!("expected {} edges listed at both endpoints, got {} entries",m,entries));
+    }
+
+    return Ok((vertices,vertex_weights));
+}
+
+/// Writes `graph` as METIS graph file, treating every edge as undirected. Edge weights are
+/// written if `L` has a field, vertex weights are not written. Fails with `InvalidInput` for
+/// self-loops, which METIS doesn't allow.
+pub fn write_metis<'a,V,L,G,W>(graph: &'a G, mut out: W) -> io::Result<()>
+    where L: Weight, G: 'a + VertexListGraph<'a,V,L> + EdgeListGraph<'a,V,L>, W: Write {
+    if L::FIELDS > 1 {
+        return Err(invalid_input(format!("METIS edge weights have 1 field, labels have {}",L::FIELDS)));
+    }
+
+    let numbers = number_vertices(graph,1);
+    let mut adjacency = vec![vec![]; graph.num_vertices()];
+
+    for e in graph.edges() {
+        let from = numbers[&graph.source(e)];
+        let to = numbers[&graph.target(e)];
+        let lb = graph.edge_label(e).unwrap();
+
+        if from == to {
+            return Err(invalid_input(format!("self-loop at vertex {}",from)));
+        }
+
+        adjacency[from - 1].push((to,lb));
+        adjacency[to - 1].push((from,lb));
+    }
+
+    if L::FIELDS == 1 {
+        writeln!(out,"{} {} 001",graph.num_vertices(),graph.num_edges())?;
+    } else {
+        writeln!(out,"{} {}",graph.num_vertices(),graph.num_edges())?;
+    }
+
+    for adj in adjacency {
+        let fields = adj.iter().map(|&(v,lb)| {
+            let mut f = vec![v.to_string()];
+
+            f.extend(lb.fields());
+            f.join(" ")
+        }).collect::<Vec<_>>();
+
+        writeln!(out,"{}",fields.join(" "))?;
+    }
+
+    return Ok(());
+}
+
+/// Reads a square Matrix Market coordinate matrix into `graph`. Every entry `i j` becomes an edge
+/// from vertex `i` to `j`.
+///
+/// `pattern` matrices need a `L` without fields, `integer` and `real` ones a one-field `L`.
+/// Entries of `symmetric` matrices are added in both directions. Complex, skew-symmetric and
+/// hermitian matrices and the dense `array` format are not supported. Returns the vertices by
+/// number.
+pub fn read_matrix_market<'a,L,H,R>(input: R, graph: &mut H) -> Result<Vec<H::Vertex>,TextError>
+    where L: Weight + Clone, H: MutableGraph<'a,usize,L>, R: BufRead {
+    let mut lines = Lines::new(input,&[]);
+    let banner = match lines.next(false)? {
+        Some(line) => line.to_lowercase(),
+        None => return lines.error("missing header".to_string()),
+    };
+    let banner = banner.split_whitespace().collect::<Vec<_>>();
+
+    if banner.len() != 5 || banner[0] != "%%matrixmarket" || banner[1] != "matrix" {
+        return lines.error("expected %%MatrixMarket matrix <format> <field> <symmetry>".to_string());
+    }
+
+    if banner[2] != "coordinate" {
+        return lines.error(format!("unsupported format {}",banner[2]));
+    }
+
+    let weights = match banner[3] {
+        "pattern" => 0,
+        "integer" | "real" | "double" => 1,
+        f => return lines.error(format!("unsupported field {}",f)),
+    };
+    let symmetric = match banner[4] {
+        "general" => false,
+        "symmetric" => true,
+        s => return lines.error(format!("unsupported symmetry {}",s)),
+    };
+
+    if weights != L::FIELDS {
+        return lines.error(format!("{} entries have {} weight fields, labels have {}",banner[3],weights,L::FIELDS));
+    }
+
+    lines.comments = &["%"];
+
+    let size = match lines.next(false)? {
+        Some(line) => line,
+        None => return lines.error("missing size line".to_string()),
+    };
+    let size = size.split_whitespace().collect::<Vec<_>>();
+
+    if size.len() != 3 || size[0] != size[1] {
+        return lines.error("expected <n> <n> <entries> of a square matrix".to_string());
+    }
+
+    let vertices = lines.add_vertices(graph,lines.number(size[0])?)?;
+    let entries: usize = lines.number(size[2])?;
+
+    for i in 0..entries {
+        let line = match lines.next(false)? {
+            Some(line) => line,
+            None => return lines.error(format!("expected {} entries, got {}",entries,i)),
+        };
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+
+        if fields.len() < 2 {
+            return lines.error("expected <row> <column> [<value>]".to_string());
+        }
+
+        let from = lines.vertex(fields[0],&vertices)?;
+        let to = lines.vertex(fields[1],&vertices)?;
+        let lb: L = lines.weight(&fields[2..])?;
+
+        if symmetric && from != to {
+            lines.add_edge(graph,lb.clone(),to,from)?;
+        }
+
+        lines.add_edge(graph,lb,from,to)?;
+    }
+
+    if lines.next(false)?.is_some() {
+        return lines.error(format!("more than {} entries",entries));
+    }
+
+    return Ok(vertices);
+}
+
+/// Writes `graph` as `general` Matrix Market coordinate matrix. The field is `pattern` if `L` has
+/// no fields and `integer` or `real` if it has one.
+pub fn write_matrix_market<'a,V,L,G,W>(graph: &'a G, mut out: W) -> io::Result<()>
+    where L: Weight, G: 'a + VertexListGraph<'a,V,L> + EdgeListGraph<'a,V,L>, W: Write {
+    let field = match (L::FIELDS,L::INTEGER) {
+        (0,_) => "pattern",
+        (1,true) => "integer",
+        (1,false) => "real",
+        (n,_) => return Err(invalid_input(format!("Matrix Market entries have at most 1 field, labels have {}",n))),
+    };
+    let numbers = number_vertices(graph,1);
+    let n = graph.num_vertices();
+
+    writeln!(out,"%%MatrixMarket matrix coordinate {} general",field)?;
+    writeln!(out,"{} {} {}",n,n,graph.num_edges())?;
+
+    for e in graph.edges() {
+        write_edge(&mut out,"",numbers[&graph.source(e)],numbers[&graph.target(e)],graph.edge_label(e).unwrap())?;
+    }
+
+    return Ok(());
+}
+
+/// Reads lines `<from> <to> [<weight>...]` into `graph`. Lines starting with `#` or `%` are
+/// comments. Vertices are numbered by arbitrary non-negative integers and added when they occur
+/// first. Returns a map from vertex numbers to vertices.
+pub fn read_edge_list<'a,L,H,R>(input: R, graph: &mut H) -> Result<HashMap<usize,H::Vertex>,TextError>
+    where L: Weight, H: MutableGraph<'a,usize,L>, R: BufRead {
+    let mut lines = Lines::new(input,&["#","%"]);
+    let mut vertices = HashMap::new();
+
+    while let Some(line) = lines.next(false)? {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+
+        if fields.len() < 2 {
+            return lines.error("expected <from> <to> [<weight>...]".to_string());
+        }
+
+        let from: usize = lines.number(fields[0])?;
+        let to: usize = lines.number(fields[1])?;
+        let lb = lines.weight(&fields[2..])?;
+        let from = *vertices.entry(from).or_insert_with(|| graph.add_vertex(from));
+        let to = *vertices.entry(to).or_insert_with(|| graph.add_vertex(to));
+
+        lines.add_edge(graph,lb,from,to)?;
+    }
+
+    return Ok(vertices);
+}
+
+/// Writes the edges of `graph` as lines `<from> <to> [<weight>...]`. Vertices are numbered from 0,
+/// isolated vertices are lost.
+pub fn write_edge_list<'a,V,L,G,W>(graph: &'a G, mut out: W) -> io::Result<()>
+    where L: Weight, G: 'a + VertexListGraph<'a,V,L> + EdgeListGraph<'a,V,L>, W: Write {
+    let numbers = number_vertices(graph,0);
+
+    for e in graph.edges() {
+        write_edge(&mut out,"",numbers[&graph.source(e)],numbers[&graph.target(e)],graph.edge_label(e).unwrap())?;
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adjacency_list::AdjacencyList;
+    use adjacency_matrix::OwnedAdjacencyMatrix;
+    use traits::{
+        Graph,
+        IncidenceGraph,
+        UndirectedEdges,
+    };
+
+    #[test]
+    fn dimacs() {
+        let input = "c shortest path\np sp 3 3\na 1 2 5\na 2 3 -1\nc done\na 1 3 7\n";
+        let mut g = AdjacencyList::<usize,i64>::new();
+        let (problem,vs) = read_dimacs(input.as_bytes(),&mut g).unwrap();
+
+        assert_eq!(problem, DimacsProblem::ShortestPath);
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.vertex_label(vs[2]), Some(&3));
+        assert_eq!(g.out_edges(vs[0]).map(|e| *g.edge_label(e).unwrap()).collect::<Vec<_>>(), vec![5,7]);
+
+        let mut buf = vec![];
+
+        write_dimacs(&g,&problem,&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "p sp 3 3\na 1 2 5\na 2 3 -1\na 1 3 7\n");
+
+        let input = "p max 4 2\nn 1 s\nn 4 t\na 1 2 10\na 2 4 3\n";
+        let mut g = AdjacencyList::<usize,u32>::new();
+        let (problem,vs) = read_dimacs(input.as_bytes(),&mut g).unwrap();
+
+        assert_eq!(problem, DimacsProblem::MaxFlow{ source: vs[0], sink: vs[3] });
+
+        let mut buf = vec![];
+
+        write_dimacs(&g,&problem,&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), input);
+
+        let input = "p min 2 1\nn 1 4\nn 2 -4\na 1 2 0 4 2.5\n";
+        let mut g = AdjacencyList::<usize,FlowArc<f64>>::new();
+        let (problem,vs) = read_dimacs(input.as_bytes(),&mut g).unwrap();
+
+        assert_eq!(problem, DimacsProblem::MinCostFlow{ supplies: vec![(vs[0],4),(vs[1],-4)] });
+        assert_eq!(g.edge_label(g.edges().next().unwrap()), Some(&FlowArc{ lower: 0.0, capacity: 4.0, cost: 2.5 }));
+
+        let mut buf = vec![];
+
+        write_dimacs(&g,&problem,&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), input);
+
+        let err = |input: &str| read_dimacs::<i64,_,_>(input.as_bytes(),&mut AdjacencyList::<usize,i64>::new()).err().map(|e| e.to_string());
+
+        assert_eq!(err("p sp 2 1\na 1 3 1\n"), Some("line 2: vertex 3 out of range".to_string()));
+        assert_eq!(err("p sp 2 2\na 1 2 1\n"), Some("line 2: expected 2 arcs, got 1".to_string()));
+        assert_eq!(err("p min 2 0\n"), Some("line 1: min arcs have 3 weight fields, labels have 1".to_string()));
+        assert_eq!(err("a 1 2 1\n"), Some("line 1: unexpected line a 1 2 1".to_string()));
+        assert_eq!(err("p max 2 0\nn 1 s\n"), Some("line 2: max problems need exactly one source and one sink".to_string()));
+        assert_eq!(err("p sp 18446744073709551615 0\n"), Some("line 1: invalid number of vertices 18446744073709551615".to_string()));
+    }
+
+    #[test]
+    fn metis() {
+        // 4-cycle with a chord, vertex and edge weights
+        let input = "% comment\n4 5 011\n1 2 3 4 1\n2 1 3 3 2 4 5\n3 2 2 4 2\n4 1 1 3 2 2 5\n";
+        let mut g = AdjacencyList::<usize,i64,UndirectedEdges>::undirected();
+        let (vs,weights) = read_metis(input.as_bytes(),&mut g).unwrap();
+
+        assert_eq!(g.num_edges(), 5);
+        assert_eq!(weights, vec![vec![1],vec![2],vec![3],vec![4]]);
+        assert_eq!(g.out_degree(vs[1]), 3);
+        assert_eq!(g.edges_between(vs[3],vs[1]).map(|e| *g.edge_label(e).unwrap()).collect::<Vec<_>>(), vec![5]);
+
+        let mut buf = vec![];
+
+        write_metis(&g,&mut buf).unwrap();
+
+        let mut h = AdjacencyList::<usize,i64,UndirectedEdges>::undirected();
+        let (ws,_) = read_metis(&buf[..],&mut h).unwrap();
+
+        assert_eq!(h.num_edges(), 5);
+        assert_eq!(h.edges_between(ws[3],ws[1]).map(|e| *h.edge_label(e).unwrap()).collect::<Vec<_>>(), vec![5]);
+
+        // isolated vertex, unweighted edges default to 1
+        let input = "3 1\n2\n1\n\n";
+        let mut g = AdjacencyList::<usize,u32,UndirectedEdges>::undirected();
+        let (vs,_) = read_metis(input.as_bytes(),&mut g).unwrap();
+
+        assert_eq!(g.out_degree(vs[2]), 0);
+        assert_eq!(g.edge_label(g.edges().next().unwrap()), Some(&1));
+
+        let mut g = AdjacencyList::<usize,(),UndirectedEdges>::undirected();
+
+        assert!(read_metis("2 1\n2\n2\n".as_bytes(),&mut g).is_err());
+        assert!(read_metis("2 2\n2\n1\n".as_bytes(),&mut g).is_err());
+
+        let err = |input: &str| read_metis(input.as_bytes(),&mut AdjacencyList::<usize,i64,UndirectedEdges>::undirected()).err().map(|e| e.to_string());
+
+        assert_eq!(err("3 1\n2\n\n1\n"), Some("line 4: edge 3 1 is not listed at vertex 1".to_string()));
+        assert_eq!(err("3 1\n2\n3\n\n"), Some("line 4: edge 1 2 is not listed at vertex 2".to_string()));
+        assert_eq!(err("2 1 001\n2 4\n1 5\n"), Some("line 3: edge 1 2 has different weights at its endpoints".to_string()));
+        assert_eq!(err("2 1 111 18446744073709551615\n2 4\n1 5\n"), Some("line 1: invalid number of vertex weights 18446744073709551615".to_string()));
+        assert_eq!(err("4611686018427387904 0\n"), Some("line 1: expected 4611686018427387904 vertex lines, got 0".to_string()));
+        assert_eq!(err("2 1\n3\n\n"), Some("line 2: vertex 3 out of range".to_string()));
+    }
+
+    #[test]
+    fn matrix_market() {
+        let input = "%%MatrixMarket matrix coordinate real symmetric\n% comment\n3 3 3\n1 1 1.5\n2 1 -2\n3 2 4e2\n";
+        let mut g = OwnedAdjacencyMatrix::<usize,f64>::new();
+        let vs = read_matrix_market(input.as_bytes(),&mut g).unwrap();
+
+        assert_eq!(g.num_edges(), 5);
+        assert_eq!(g.out_edges(vs[1]).map(|e| *g.edge_label(e).unwrap()).collect::<Vec<_>>(), vec![-2.0,400.0]);
+
+        let mut buf = vec![];
+
+        write_matrix_market(&g,&mut buf).unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+
+        assert!(out.starts_with("%%MatrixMarket matrix coordinate real general\n3 3 5\n"));
+
+        let mut h = AdjacencyList::<usize,f64>::new();
+
+        read_matrix_market(out.as_bytes(),&mut h).unwrap();
+        assert_eq!(h.num_edges(), 5);
+
+        let mut p = AdjacencyList::<usize,()>::new();
+
+        assert!(read_matrix_market(input.as_bytes(),&mut p).is_err());
+        assert!(read_matrix_market("%%MatrixMarket matrix coordinate pattern general\n2 2 1\n1 2\n".as_bytes(),&mut p).is_ok());
+        assert!(read_matrix_market("%%MatrixMarket matrix array real general\n2 2\n".as_bytes(),&mut h).is_err());
+
+        // matrices hold one edge per pair
+        let mut g = OwnedAdjacencyMatrix::<usize,i32>::new();
+        let err = read_matrix_market("%%MatrixMarket matrix coordinate integer general\n2 2 2\n1 2 1\n1 2 3\n".as_bytes(),&mut g);
+
+        assert_eq!(err.err().map(|e| e.to_string()), Some("line 4: edge rejected: edge already exists".to_string()));
+    }
+
+    #[test]
+    fn edge_list() {
+        let input = "# snap style\n10 20\n20 30\n10 30\n30 10\n";
+        let mut g = AdjacencyList::<usize,()>::new();
+        let vs = read_edge_list(input.as_bytes(),&mut g).unwrap();
+
+        assert_eq!(g.num_vertices(), 3);
+        assert_eq!(g.num_edges(), 4);
+        assert_eq!(g.vertex_label(vs[&30]), Some(&30));
+
+        let mut buf = vec![];
+
+        write_edge_list(&g,&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "0 1\n1 2\n0 2\n2 0\n");
+
+        let mut g = AdjacencyList::<usize,f32>::new();
+
+        assert!(read_edge_list("1 2 0.5\n2 3\n".as_bytes(),&mut g).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use std::hash::Hash;
 
+use error::GraphError;
+
 pub trait Graph<'a,V,E> {
     type Vertex: Clone + Hash + PartialEq + Eq + Ord + Copy;
     type Edge: Clone + Hash + PartialEq + Eq + Copy;
@@ -9,23 +11,75 @@ pub trait Graph<'a,V,E> {
     fn vertex_label(&self,Self::Vertex) -> Option<&V>;
     fn source(&self,Self::Edge) -> Self::Vertex;
     fn target(&self,Self::Edge) -> Self::Vertex;
+
+    fn try_edge_label(&self, e: Self::Edge) -> Result<&E,GraphError> {
+        return self.edge_label(e).ok_or(GraphError::UnknownEdge);
+    }
+
+    fn try_vertex_label(&self, v: Self::Vertex) -> Result<&V,GraphError> {
+        return self.vertex_label(v).ok_or(GraphError::UnknownVertex);
+    }
+
+    fn try_source(&self, e: Self::Edge) -> Result<Self::Vertex,GraphError> {
+        return self.try_edge_label(e).map(|_| self.source(e));
+    }
+
+    fn try_target(&self, e: Self::Edge) -> Result<Self::Vertex,GraphError> {
+        return self.try_edge_label(e).map(|_| self.target(e));
+    }
+
+    /// The endpoint of `e` that is not `v`, `v` itself for self-loops. Following an edge from
+    /// `out_edges(v)` to `opposite(e,v)` works for directed as well as undirected graphs.
+    fn opposite(&self, e: Self::Edge, v: Self::Vertex) -> Self::Vertex {
+        let s = self.source(e);
+
+        if s == v {
+            return self.target(e);
+        } else {
+            return s;
+        }
+    }
 }
 
 pub trait IncidenceGraph<'a,V,E>: Graph<'a,V,E> {
     type Incidence: Iterator<Item=Self::Edge>;
     fn out_degree(&'a self, Self::Vertex) -> usize;
     fn out_edges(&'a self, Self::Vertex) -> Self::Incidence;
+
+    fn try_out_degree(&'a self, v: Self::Vertex) -> Result<usize,GraphError> {
+        return self.try_vertex_label(v).map(|_| self.out_degree(v));
+    }
+
+    fn try_out_edges(&'a self, v: Self::Vertex) -> Result<Self::Incidence,GraphError> {
+        return self.try_vertex_label(v).map(|_| self.out_edges(v));
+    }
 }
 
 pub trait BidirectionalGraph<'a,V,E>: IncidenceGraph<'a,V,E> {
     fn in_degree(&'a self, Self::Vertex) -> usize;
     fn degree(&'a self, Self::Vertex) -> usize;
     fn in_edges(&'a self, Self::Vertex) -> Self::Incidence;
+
+    fn try_in_degree(&'a self, v: Self::Vertex) -> Result<usize,GraphError> {
+        return self.try_vertex_label(v).map(|_| self.in_degree(v));
+    }
+
+    fn try_degree(&'a self, v: Self::Vertex) -> Result<usize,GraphError> {
+        return self.try_vertex_label(v).map(|_| self.degree(v));
+    }
+
+    fn try_in_edges(&'a self, v: Self::Vertex) -> Result<Self::Incidence,GraphError> {
+        return self.try_vertex_label(v).map(|_| self.in_edges(v));
+    }
 }
 
 pub trait AdjacencyGraph<'a,V,E>: Graph<'a,V,E> {
     type Adjacency: Iterator<Item=Self::Vertex>;
     fn adjacent_vertices(&'a self, Self::Vertex) -> Self::Adjacency;
+
+    fn try_adjacent_vertices(&'a self, v: Self::Vertex) -> Result<Self::Adjacency,GraphError> {
+        return self.try_vertex_label(v).map(|_| self.adjacent_vertices(v));
+    }
 }
 
 pub trait VertexListGraph<'a,V,E>: IncidenceGraph<'a,V,E> + AdjacencyGraph<'a,V,E> {
@@ -51,4 +105,68 @@ pub trait MutableGraph<'a,V,E>: Graph<'a,V,E> {
     fn remove_edge(&mut self,Self::Edge) -> Option<E>;
     fn edge_label_mut(&mut self,Self::Edge) -> Option<&mut E>;
     fn vertex_label_mut(&mut self,Self::Vertex) -> Option<&mut V>;
+
+    /// `add_edge` reporting why the edge was not added. The default implementation returns
+    /// `UnknownVertex` if an endpoint is missing and `EdgeRejected` if `add_edge` fails otherwise.
+    fn try_add_edge(&mut self, lb: E, from: Self::Vertex, to: Self::Vertex) -> Result<Self::Edge,GraphError> {
+        self.try_vertex_label(from)?;
+        self.try_vertex_label(to)?;
+
+        return self.add_edge(lb,from,to).ok_or(GraphError::EdgeRejected);
+    }
+
+    fn try_remove_vertex(&mut self, v: Self::Vertex) -> Result<V,GraphError> {
+        return self.remove_vertex(v).ok_or(GraphError::UnknownVertex);
+    }
+
+    fn try_remove_edge(&mut self, e: Self::Edge) -> Result<E,GraphError> {
+        return self.remove_edge(e).ok_or(GraphError::UnknownEdge);
+    }
+}
+
+/// Graphs whose edges have no direction. `out_edges` and `in_edges` both list every edge
+/// incident to a vertex, self-loops once, and `out_degree` and `in_degree` are their lengths.
+/// `degree` counts self-loops twice. `source` and `target` return the endpoints in the order the
+/// edge was added, use `opposite` to get the other end of an edge.
+pub trait UndirectedGraph<'a,V,E>: BidirectionalGraph<'a,V,E> {}
+
+/// Direction of the edges of a graph type, see `DirectedEdges` and `UndirectedEdges`.
+pub trait Direction {
+    const DIRECTED: bool;
+}
+
+/// Marker for graphs with directed edges.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct DirectedEdges;
+
+/// Marker for graphs with undirected edges.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct UndirectedEdges;
+
+impl Direction for DirectedEdges {
+    const DIRECTED: bool = true;
+}
+
+impl Direction for UndirectedEdges {
+    const DIRECTED: bool = false;
+}
+
+/// Graphs whose vertices can be numbered densely. The `_indexed` variants of the algorithms use
+/// the index to keep per-vertex state in vectors and bit sets instead of hash maps.
+pub trait VertexIndexGraph<'a,V,E>: Graph<'a,V,E> {
+    /// Index of the vertex, smaller than `vertex_index_bound()`. Different vertices have
+    /// different indices.
+    fn vertex_index(&self,Self::Vertex) -> usize;
+    /// Upper bound of all vertex indices. Can be larger than the number of vertices if the
+    /// graph has holes, e.g. after vertices were removed.
+    fn vertex_index_bound(&self) -> usize;
+}
+
+/// Graphs whose edges can be numbered densely.
+pub trait EdgeIndexGraph<'a,V,E>: Graph<'a,V,E> {
+    /// Index of the edge, smaller than `edge_index_bound()`. Different edges have different
+    /// indices.
+    fn edge_index(&self,Self::Edge) -> usize;
+    /// Upper bound of all edge indices.
+    fn edge_index_bound(&self) -> usize;
 }
@@ -0,0 +1,692 @@
+//! Zero-copy adaptors that present another graph with a different shape.
+//!
+//! `Reversed` flips the direction of all edges, `Undirected` ignores it and `Filtered` hides
+//! vertices and edges that do not satisfy a predicate. An induced subgraph is a `Filtered` view
+//! with a `VertexSet` filter. Views keep the descriptors of the underlying graph, results
+//! computed on a view can be used with the graph directly.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+use traits::*;
+
+/// The graph with all edges reversed: `source` and `target` as well as `in_edges` and
+/// `out_edges` are swapped. Running `immediate_dominator` on the reversed graph yields
+/// post-dominators.
+#[derive(Debug)]
+pub struct Reversed<'a,G: 'a> {
+    graph: &'a G,
+}
+
+impl<'a,G: 'a> Reversed<'a,G> {
+    pub fn new(graph: &'a G) -> Self {
+        return Reversed{ graph: graph };
+    }
+}
+
+impl<'a,G: 'a> Clone for Reversed<'a,G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a,G: 'a> Copy for Reversed<'a,G> {}
+
+impl<'a,V,E,G: 'a + Graph<'a,V,E>> Graph<'a,V,E> for Reversed<'a,G> {
+    type Vertex = G::Vertex;
+    type Edge = G::Edge;
+
+    fn edge_label(&self, e: Self::Edge) -> Option<&E> {
+        return self.graph.edge_label(e);
+    }
+
+    fn vertex_label(&self, v: Self::Vertex) -> Option<&V> {
+        return self.graph.vertex_label(v);
+    }
+
+    fn source(&self, e: Self::Edge) -> Self::Vertex {
+        return self.graph.target(e);
+    }
+
+    fn target(&self, e: Self::Edge) -> Self::Vertex {
+        return self.graph.source(e);
+    }
+
+    fn try_source(&self, e: Self::Edge) -> Result<Self::Vertex,::GraphError> {
+        return self.graph.try_target(e);
+    }
+
+    fn try_target(&self, e: Self::Edge) -> Result<Self::Vertex,::GraphError> {
+        return self.graph.try_source(e);
+    }
+}
+
+impl<'a,V,E,G: 'a + BidirectionalGraph<'a,V,E>> IncidenceGraph<'a,V,E> for Reversed<'a,G> {
+    type Incidence = G::Incidence;
+
+    fn out_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.graph.in_degree(v);
+    }
+
+    fn out_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return self.graph.in_edges(v);
+    }
+}
+
+impl<'a,V,E,G: 'a + BidirectionalGraph<'a,V,E>> BidirectionalGraph<'a,V,E> for Reversed<'a,G> {
+    fn in_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.graph.out_degree(v);
+    }
+
+    fn degree(&'a self, v: Self::Vertex) -> usize {
+        return self.graph.degree(v);
+    }
+
+    fn in_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return self.graph.out_edges(v);
+    }
+}
+
+impl<'a,V,E,G: 'a + AdjacencyGraph<'a,V,E>> AdjacencyGraph<'a,V,E> for Reversed<'a,G> {
+    type Adjacency = G::Adjacency;
+
+    fn adjacent_vertices(&'a self, v: Self::Vertex) -> Self::Adjacency {
+        return self.graph.adjacent_vertices(v);
+    }
+}
+
+impl<'a,V,E,G: 'a + VertexListGraph<'a,V,E> + BidirectionalGraph<'a,V,E>> VertexListGraph<'a,V,E> for Reversed<'a,G> {
+    type Vertices = G::Vertices;
+
+    fn vertices(&'a self) -> Self::Vertices {
+        return self.graph.vertices();
+    }
+
+    fn num_vertices(&self) -> usize {
+        return self.graph.num_vertices();
+    }
+}
+
+impl<'a,V,E,G: 'a + EdgeListGraph<'a,V,E>> EdgeListGraph<'a,V,E> for Reversed<'a,G> {
+    type Edges = G::Edges;
+
+    fn num_edges(&self) -> usize {
+        return self.graph.num_edges();
+    }
+
+    fn edges(&'a self) -> Self::Edges {
+        return self.graph.edges();
+    }
+}
+
+impl<'a,V,E,G: 'a + AdjacencyMatrixGraph<'a,V,E>> AdjacencyMatrixGraph<'a,V,E> for Reversed<'a,G> {
+    fn edge(&'a self, from: Self::Vertex, to: Self::Vertex) -> Option<Self::Edge> {
+        return self.graph.edge(to,from);
+    }
+}
+
+impl<'a,V,E,G: 'a + VertexIndexGraph<'a,V,E>> VertexIndexGraph<'a,V,E> for Reversed<'a,G> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return self.graph.vertex_index(v);
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.graph.vertex_index_bound();
+    }
+}
+
+impl<'a,V,E,G: 'a + EdgeIndexGraph<'a,V,E>> EdgeIndexGraph<'a,V,E> for Reversed<'a,G> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return self.graph.edge_index(e);
+    }
+
+    fn edge_index_bound(&self) -> usize {
+        return self.graph.edge_index_bound();
+    }
+}
+
+impl<'a,V,E,G: 'a + UndirectedGraph<'a,V,E>> UndirectedGraph<'a,V,E> for Reversed<'a,G> {}
+
+/// The graph with edge directions ignored. `out_edges` and `in_edges` both return all edges
+/// incident to a vertex once, also if the underlying graph is undirected already. Use `opposite`
+/// to get the neighbor on the other end. `source` and `target` are the ones of the underlying
+/// graph.
+#[derive(Debug)]
+pub struct Undirected<'a,G: 'a> {
+    graph: &'a G,
+}
+
+impl<'a,G: 'a> Undirected<'a,G> {
+    pub fn new(graph: &'a G) -> Self {
+        return Undirected{ graph: graph };
+    }
+}
+
+impl<'a,G: 'a> Clone for Undirected<'a,G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a,G: 'a> Copy for Undirected<'a,G> {}
+
+impl<'a,V,E,G: 'a + Graph<'a,V,E>> Graph<'a,V,E> for Undirected<'a,G> {
+    type Vertex = G::Vertex;
+    type Edge = G::Edge;
+
+    fn edge_label(&self, e: Self::Edge) -> Option<&E> {
+        return self.graph.edge_label(e);
+    }
+
+    fn vertex_label(&self, v: Self::Vertex) -> Option<&V> {
+        return self.graph.vertex_label(v);
+    }
+
+    fn source(&self, e: Self::Edge) -> Self::Vertex {
+        return self.graph.source(e);
+    }
+
+    fn target(&self, e: Self::Edge) -> Self::Vertex {
+        return self.graph.target(e);
+    }
+
+    fn try_source(&self, e: Self::Edge) -> Result<Self::Vertex,::GraphError> {
+        return self.graph.try_source(e);
+    }
+
+    fn try_target(&self, e: Self::Edge) -> Result<Self::Vertex,::GraphError> {
+        return self.graph.try_target(e);
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + BidirectionalGraph<'a,V,E>> IncidenceGraph<'a,V,E> for Undirected<'a,G> {
+    type Incidence = Box<Iterator<Item=G::Edge> + 'a>;
+
+    fn out_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.out_edges(v).count();
+    }
+
+    fn out_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        // self-loops are in both lists, every edge of an undirected graph is
+        let mut seen = HashSet::new();
+
+        return Box::new(self.graph.out_edges(v).chain(self.graph.in_edges(v)).filter(move |&e| seen.insert(e)));
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + BidirectionalGraph<'a,V,E>> BidirectionalGraph<'a,V,E> for Undirected<'a,G> {
+    fn in_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.out_degree(v);
+    }
+
+    fn degree(&'a self, v: Self::Vertex) -> usize {
+        // self-loops count twice
+        return self.out_edges(v).map(|e| if self.opposite(e,v) == v { 2 } else { 1 }).sum();
+    }
+
+    fn in_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return self.out_edges(v);
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + BidirectionalGraph<'a,V,E>> UndirectedGraph<'a,V,E> for Undirected<'a,G> {}
+
+impl<'a,V: 'a,E: 'a,G: 'a + BidirectionalGraph<'a,V,E>> AdjacencyGraph<'a,V,E> for Undirected<'a,G> {
+    type Adjacency = Box<Iterator<Item=G::Vertex> + 'a>;
+
+    fn adjacent_vertices(&'a self, v: Self::Vertex) -> Self::Adjacency {
+        let mut seen = HashSet::new();
+
+        return Box::new(self.out_edges(v).map(move |e| self.opposite(e,v)).filter(move |&w| seen.insert(w)));
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + VertexListGraph<'a,V,E> + BidirectionalGraph<'a,V,E>> VertexListGraph<'a,V,E> for Undirected<'a,G> {
+    type Vertices = G::Vertices;
+
+    fn vertices(&'a self) -> Self::Vertices {
+        return self.graph.vertices();
+    }
+
+    fn num_vertices(&self) -> usize {
+        return self.graph.num_vertices();
+    }
+}
+
+impl<'a,V,E,G: 'a + EdgeListGraph<'a,V,E>> EdgeListGraph<'a,V,E> for Undirected<'a,G> {
+    type Edges = G::Edges;
+
+    fn num_edges(&self) -> usize {
+        return self.graph.num_edges();
+    }
+
+    fn edges(&'a self) -> Self::Edges {
+        return self.graph.edges();
+    }
+}
+
+impl<'a,V,E,G: 'a + AdjacencyMatrixGraph<'a,V,E>> AdjacencyMatrixGraph<'a,V,E> for Undirected<'a,G> {
+    fn edge(&'a self, from: Self::Vertex, to: Self::Vertex) -> Option<Self::Edge> {
+        return self.graph.edge(from,to).or_else(|| self.graph.edge(to,from));
+    }
+}
+
+impl<'a,V,E,G: 'a + VertexIndexGraph<'a,V,E>> VertexIndexGraph<'a,V,E> for Undirected<'a,G> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return self.graph.vertex_index(v);
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.graph.vertex_index_bound();
+    }
+}
+
+impl<'a,V,E,G: 'a + EdgeIndexGraph<'a,V,E>> EdgeIndexGraph<'a,V,E> for Undirected<'a,G> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return self.graph.edge_index(e);
+    }
+
+    fn edge_index_bound(&self) -> usize {
+        return self.graph.edge_index_bound();
+    }
+}
+
+/// Decides which vertices and edges a `Filtered` view shows.
+pub trait ViewFilter<Vx,Ed> {
+    fn keep_vertex(&self,Vx) -> bool;
+    fn keep_edge(&self,Ed) -> bool;
+}
+
+/// Filter defined by a vertex and an edge predicate.
+#[derive(Clone,Debug)]
+pub struct Predicates<FV,FE> {
+    vertex: FV,
+    edge: FE,
+}
+
+impl<Vx,Ed,FV: Fn(Vx) -> bool,FE: Fn(Ed) -> bool> ViewFilter<Vx,Ed> for Predicates<FV,FE> {
+    fn keep_vertex(&self, v: Vx) -> bool {
+        return (self.vertex)(v);
+    }
+
+    fn keep_edge(&self, e: Ed) -> bool {
+        return (self.edge)(e);
+    }
+}
+
+/// Filter that keeps a fixed set of vertices and all edges between them.
+#[derive(Clone,Debug)]
+pub struct VertexSet<Vx: Hash + Eq> {
+    vertices: HashSet<Vx>,
+}
+
+impl<Vx: Hash + Eq,Ed> ViewFilter<Vx,Ed> for VertexSet<Vx> {
+    fn keep_vertex(&self, v: Vx) -> bool {
+        return self.vertices.contains(&v);
+    }
+
+    fn keep_edge(&self, _: Ed) -> bool {
+        return true;
+    }
+}
+
+/// Subgraph of all vertices and edges accepted by a `ViewFilter`. An edge is only visible if
+/// both of its endpoints are. Hidden vertices and edges behave as if they were not part of the
+/// graph, e.g. `vertex_label` returns `None` for them.
+#[derive(Clone,Debug)]
+pub struct Filtered<'a,G: 'a,F> {
+    graph: &'a G,
+    filter: F,
+}
+
+/// Subgraph induced by a set of vertices.
+pub type InducedSubgraph<'a,G,Vx> = Filtered<'a,G,VertexSet<Vx>>;
+
+impl<'a,G: 'a,FV,FE> Filtered<'a,G,Predicates<FV,FE>> {
+    /// View of the vertices and edges of `graph` for which `vertex` resp. `edge` return true.
+    pub fn new(graph: &'a G, vertex: FV, edge: FE) -> Self {
+        return Filtered{
+            graph: graph,
+            filter: Predicates{ vertex: vertex, edge: edge },
+        };
+    }
+}
+
+impl<'a,G: 'a,F> Filtered<'a,G,F> {
+    /// View of `graph` filtered by a custom `ViewFilter`.
+    pub fn with_filter(graph: &'a G, filter: F) -> Self {
+        return Filtered{
+            graph: graph,
+            filter: filter,
+        };
+    }
+}
+
+impl<'a,G: 'a,Vx: Hash + Eq> Filtered<'a,G,VertexSet<Vx>> {
+    /// Subgraph of `graph` induced by `vertices`.
+    pub fn induced<I: IntoIterator<Item=Vx>>(graph: &'a G, vertices: I) -> Self {
+        return Filtered{
+            graph: graph,
+            filter: VertexSet{ vertices: HashSet::from_iter(vertices) },
+        };
+    }
+}
+
+fn has_vertex<'a,V,E,G: 'a + Graph<'a,V,E>,F: ViewFilter<G::Vertex,G::Edge>>(view: &Filtered<'a,G,F>, v: G::Vertex) -> bool {
+    return view.graph.vertex_label(v).is_some() && view.filter.keep_vertex(v);
+}
+
+fn has_edge<'a,V,E,G: 'a + Graph<'a,V,E>,F: ViewFilter<G::Vertex,G::Edge>>(view: &Filtered<'a,G,F>, e: G::Edge) -> bool {
+    return view.graph.edge_label(e).is_some() && view.filter.keep_edge(e) &&
+           view.filter.keep_vertex(view.graph.source(e)) && view.filter.keep_vertex(view.graph.target(e));
+}
+
+impl<'a,V,E,G: 'a + Graph<'a,V,E>,F: ViewFilter<G::Vertex,G::Edge>> Graph<'a,V,E> for Filtered<'a,G,F> {
+    type Vertex = G::Vertex;
+    type Edge = G::Edge;
+
+    fn edge_label(&self, e: Self::Edge) -> Option<&E> {
+        if has_edge(self,e) {
+            return self.graph.edge_label(e);
+        } else {
+            return None;
+        }
+    }
+
+    fn vertex_label(&self, v: Self::Vertex) -> Option<&V> {
+        if has_vertex(self,v) {
+            return self.graph.vertex_label(v);
+        } else {
+            return None;
+        }
+    }
+
+    fn source(&self, e: Self::Edge) -> Self::Vertex {
+        return self.graph.source(e);
+    }
+
+    fn target(&self, e: Self::Edge) -> Self::Vertex {
+        return self.graph.target(e);
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + IncidenceGraph<'a,V,E>,F: 'a + ViewFilter<G::Vertex,G::Edge>> IncidenceGraph<'a,V,E> for Filtered<'a,G,F> {
+    type Incidence = Box<Iterator<Item=G::Edge> + 'a>;
+
+    fn out_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.out_edges(v).count();
+    }
+
+    fn out_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return Box::new(self.graph.out_edges(v).filter(move |&e| has_edge(self,e)));
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + BidirectionalGraph<'a,V,E>,F: 'a + ViewFilter<G::Vertex,G::Edge>> BidirectionalGraph<'a,V,E> for Filtered<'a,G,F> {
+    fn in_degree(&'a self, v: Self::Vertex) -> usize {
+        return self.in_edges(v).count();
+    }
+
+    fn degree(&'a self, v: Self::Vertex) -> usize {
+        return self.in_degree(v) + self.out_degree(v);
+    }
+
+    fn in_edges(&'a self, v: Self::Vertex) -> Self::Incidence {
+        return Box::new(self.graph.in_edges(v).filter(move |&e| has_edge(self,e)));
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + UndirectedGraph<'a,V,E>,F: 'a + ViewFilter<G::Vertex,G::Edge>> UndirectedGraph<'a,V,E> for Filtered<'a,G,F> {}
+
+impl<'a,V: 'a,E: 'a,G: 'a + BidirectionalGraph<'a,V,E>,F: 'a + ViewFilter<G::Vertex,G::Edge>> AdjacencyGraph<'a,V,E> for Filtered<'a,G,F> {
+    type Adjacency = ::std::vec::IntoIter<G::Vertex>;
+
+    fn adjacent_vertices(&'a self, v: Self::Vertex) -> Self::Adjacency {
+        let i = self.out_edges(v).map(|x| self.graph.opposite(x,v));
+        let o = self.in_edges(v).map(|x| self.graph.opposite(x,v));
+        let mut raw = i.chain(o).collect::<Vec<G::Vertex>>();
+
+        raw.sort();
+        raw.dedup();
+
+        return raw.into_iter();
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + VertexListGraph<'a,V,E> + BidirectionalGraph<'a,V,E>,F: 'a + ViewFilter<G::Vertex,G::Edge>> VertexListGraph<'a,V,E> for Filtered<'a,G,F> {
+    type Vertices = Box<Iterator<Item=G::Vertex> + 'a>;
+
+    fn vertices(&'a self) -> Self::Vertices {
+        return Box::new(self.graph.vertices().filter(move |&v| self.filter.keep_vertex(v)));
+    }
+
+    fn num_vertices(&self) -> usize {
+        return self.graph.vertices().filter(|&v| self.filter.keep_vertex(v)).count();
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + EdgeListGraph<'a,V,E>,F: 'a + ViewFilter<G::Vertex,G::Edge>> EdgeListGraph<'a,V,E> for Filtered<'a,G,F> {
+    type Edges = Box<Iterator<Item=G::Edge> + 'a>;
+
+    fn num_edges(&self) -> usize {
+        return self.graph.edges().filter(|&e| has_edge(self,e)).count();
+    }
+
+    fn edges(&'a self) -> Self::Edges {
+        return Box::new(self.graph.edges().filter(move |&e| has_edge(self,e)));
+    }
+}
+
+impl<'a,V: 'a,E: 'a,G: 'a + IncidenceGraph<'a,V,E>,F: 'a + ViewFilter<G::Vertex,G::Edge>> AdjacencyMatrixGraph<'a,V,E> for Filtered<'a,G,F> {
+    fn edge(&'a self, from: Self::Vertex, to: Self::Vertex) -> Option<Self::Edge> {
+        if !has_vertex(self,from) {
+            return None;
+        }
+
+        return self.out_edges(from).find(|&e| self.graph.opposite(e,from) == to);
+    }
+}
+
+impl<'a,V,E,G: 'a + VertexIndexGraph<'a,V,E>,F: ViewFilter<G::Vertex,G::Edge>> VertexIndexGraph<'a,V,E> for Filtered<'a,G,F> {
+    fn vertex_index(&self, v: Self::Vertex) -> usize {
+        return self.graph.vertex_index(v);
+    }
+
+    fn vertex_index_bound(&self) -> usize {
+        return self.graph.vertex_index_bound();
+    }
+}
+
+impl<'a,V,E,G: 'a + EdgeIndexGraph<'a,V,E>,F: ViewFilter<G::Vertex,G::Edge>> EdgeIndexGraph<'a,V,E> for Filtered<'a,G,F> {
+    fn edge_index(&self, e: Self::Edge) -> usize {
+        return self.graph.edge_index(e);
+    }
+
+    fn edge_index_bound(&self) -> usize {
+        return self.graph.edge_index_bound();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adjacency_list::AdjacencyList;
+    use dominator::{
+        dominators,
+        immediate_dominator,
+    };
+    use search::{
+        TreeIterator,
+        TraversalOrder,
+        is_connected,
+    };
+
+    #[test]
+    fn post_dominators() {
+        // diamond with an early exit: 1 -> 2, 1 -> 3, 2 -> 4, 3 -> 4, 1 -> 4
+        let mut g = AdjacencyList::<usize,()>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+
+        let e12 = g.add_edge((),v1,v2).unwrap();
+        g.add_edge((),v1,v3);
+        g.add_edge((),v2,v4);
+        g.add_edge((),v3,v4);
+        g.add_edge((),v1,v4);
+
+        let rev = Reversed::new(&g);
+
+        assert_eq!(rev.source(e12), v2);
+        assert_eq!(rev.target(e12), v1);
+        assert_eq!(rev.out_degree(v4), 3);
+        assert_eq!(rev.in_degree(v4), 0);
+        assert_eq!(rev.edge(v2,v1), Some(e12));
+        assert_eq!(rev.edge(v1,v2), None);
+
+        let pdom = immediate_dominator(v4,&rev);
+
+        assert_eq!(pdom.len(), 4);
+        assert_eq!(pdom[&v1], v4);
+        assert_eq!(pdom[&v2], v4);
+        assert_eq!(pdom[&v3], v4);
+
+        let post = TreeIterator::new(v4,TraversalOrder::Preorder,&rev).collect::<Vec<_>>();
+        assert_eq!(post.len(), 4);
+        assert_eq!(post[0], v4);
+    }
+
+    #[test]
+    fn undirected() {
+        let mut g = AdjacencyList::<usize,()>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+
+        let e21 = g.add_edge((),v2,v1).unwrap();
+        let e23 = g.add_edge((),v2,v3).unwrap();
+        let e22 = g.add_edge((),v2,v2).unwrap();
+        let e43 = g.add_edge((),v4,v3).unwrap();
+
+        let u = Undirected::new(&g);
+
+        assert_eq!(u.out_edges(v2).collect::<Vec<_>>(), vec![e21,e23,e22]);
+        assert_eq!(u.in_edges(v1).collect::<Vec<_>>(), vec![e21]);
+        assert_eq!(u.out_degree(v3), 2);
+        assert_eq!(u.out_degree(v2), 3);
+        assert_eq!(u.degree(v2), 4);
+        assert_eq!(u.opposite(e21,v1), v2);
+        assert_eq!(u.opposite(e22,v2), v2);
+        assert_eq!(u.edge(v3,v4), Some(e43));
+        assert_eq!(u.num_edges(), 4);
+
+        let pre = TreeIterator::new(v1,TraversalOrder::Preorder,&u).collect::<Vec<_>>();
+        assert_eq!(pre, vec![v1,v2,v3,v4]);
+
+        let pre = TreeIterator::new(v1,TraversalOrder::Preorder,&g).collect::<Vec<_>>();
+        assert_eq!(pre, vec![v1]);
+
+        let e24 = g.add_edge((),v2,v4).unwrap();
+        let e42 = g.add_edge((),v4,v2).unwrap();
+        let u = Undirected::new(&g);
+
+        assert_eq!(u.adjacent_vertices(v2).collect::<Vec<_>>(), vec![v1,v3,v2,v4]);
+        assert_eq!(u.adjacent_vertices(v4).collect::<Vec<_>>(), vec![v3,v2]);
+        assert_eq!(u.out_edges(v4).collect::<Vec<_>>(), vec![e43,e42,e24]);
+    }
+
+    #[test]
+    fn undirected_of_undirected() {
+        let mut g = AdjacencyList::<usize,(),UndirectedEdges>::undirected();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+
+        let e12 = g.add_edge((),v1,v2).unwrap();
+        let e23 = g.add_edge((),v2,v3).unwrap();
+        let e22 = g.add_edge((),v2,v2).unwrap();
+
+        let u = Undirected::new(&g);
+
+        assert_eq!(u.out_edges(v2).collect::<Vec<_>>(), g.out_edges(v2).collect::<Vec<_>>());
+        assert_eq!(u.in_edges(v1).collect::<Vec<_>>(), vec![e12]);
+        assert_eq!(u.out_degree(v2), 3);
+        assert_eq!(u.in_degree(v3), 1);
+        assert_eq!(u.degree(v2), g.degree(v2));
+        assert_eq!(u.degree(v2), 4);
+        assert_eq!(u.adjacent_vertices(v2).count(), 3);
+        assert_eq!(u.opposite(e23,v3), v2);
+        assert_eq!(u.opposite(e22,v2), v2);
+    }
+
+    #[test]
+    fn filtered() {
+        let mut g = AdjacencyList::<usize,usize>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+
+        let e12 = g.add_edge(12,v1,v2).unwrap();
+        let e13 = g.add_edge(13,v1,v3).unwrap();
+        let e24 = g.add_edge(24,v2,v4).unwrap();
+        let e34 = g.add_edge(34,v3,v4).unwrap();
+
+        let view = Filtered::new(&g,|v| v != v3,|e| g.edge_label(e) != Some(&24));
+
+        assert_eq!(view.num_vertices(), 3);
+        assert_eq!(view.num_edges(), 1);
+        assert_eq!(view.vertices().collect::<Vec<_>>(), vec![v1,v2,v4]);
+        assert_eq!(view.edges().collect::<Vec<_>>(), vec![e12]);
+        assert_eq!(view.out_edges(v1).collect::<Vec<_>>(), vec![e12]);
+        assert_eq!(view.in_degree(v4), 0);
+        assert_eq!(view.vertex_label(v3), None);
+        assert_eq!(view.edge_label(e13), None);
+        assert_eq!(view.edge_label(e24), None);
+        assert_eq!(view.edge_label(e34), None);
+        assert_eq!(view.edge_label(e12), Some(&12));
+        assert_eq!(view.edge(v2,v4), None);
+        assert!(!is_connected(&view));
+
+        let pre = TreeIterator::new(v1,TraversalOrder::Preorder,&view).collect::<Vec<_>>();
+        assert_eq!(pre, vec![v1,v2]);
+    }
+
+    #[test]
+    fn induced() {
+        let mut g = AdjacencyList::<usize,()>::new();
+        let v1 = g.add_vertex(1);
+        let v2 = g.add_vertex(2);
+        let v3 = g.add_vertex(3);
+        let v4 = g.add_vertex(4);
+        let v5 = g.add_vertex(5);
+
+        g.add_edge((),v5,v1);
+        g.add_edge((),v1,v2);
+        g.add_edge((),v1,v3);
+        g.add_edge((),v2,v4);
+        g.add_edge((),v3,v4);
+        g.add_edge((),v4,v5);
+
+        let sub = Filtered::induced(&g,vec![v1,v2,v3,v4]);
+
+        assert_eq!(sub.num_vertices(), 4);
+        assert_eq!(sub.num_edges(), 4);
+        assert_eq!(sub.in_degree(v1), 0);
+        assert!(is_connected(&sub));
+
+        let dom = dominators(v1,&sub);
+
+        assert_eq!(dom.len(), 4);
+        assert_eq!(dom[&v4], vec![v1,v4]);
+
+        let rev_sub = Reversed::new(&sub);
+        let pdom = immediate_dominator(v4,&rev_sub);
+
+        assert_eq!(pdom.len(), 4);
+        assert_eq!(pdom[&v1], v4);
+    }
+}